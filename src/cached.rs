@@ -0,0 +1,232 @@
+//! A Fenwick Tree with cached prefix sums for read-heavy workloads.
+//!
+
+use std::ops::Add;
+use std::ops::Sub;
+use std::ops::AddAssign;
+use std::ops::SubAssign;
+use std::cmp::PartialOrd;
+use std::sync::OnceLock;
+
+use crate::Fenwick;
+
+/// A `Fenwick` tree that caches every prefix sum, for workloads where the
+/// tree is rarely updated but queried constantly. The first query after an
+/// update builds the cache in `O(n)` time-complexity; until the next update,
+/// prefix sums and range sums then take `O(1)`. Any update invalidates the
+/// whole cache.
+///
+/// The cache costs one extra `T` per element while it's built. It's held in
+/// a `OnceLock`, so queries can build it through a shared reference and the
+/// wrapper stays `Sync` for sharing between threads. For trees that are
+/// never updated after they're built, `FrozenCachedFenwick` computes the
+/// prefix sums up front instead.
+///
+#[derive(Debug, Clone)]
+pub struct CachedFenwick<T> {
+    fw    : Fenwick<T>,
+    cache : OnceLock<Vec<T>>,
+}
+
+impl<T> CachedFenwick<T>
+where
+    T: Add<Output = T> + Sub<Output = T> + AddAssign + SubAssign +
+       PartialOrd + Default + Copy,
+{
+    /// Creates a new zero-filled tree. The size is adjusted as in
+    /// `Fenwick::new()`.
+    ///
+    pub fn new(size: usize) -> Self {
+        Self::from(Fenwick::new(size))
+    }
+
+    /// Returns the underlying tree, dropping the cache.
+    ///
+    pub fn into_inner(self) -> Fenwick<T> {
+        self.fw
+    }
+
+    /// Returns the index of the last element.
+    ///
+    pub fn end(&self) -> usize {
+        self.fw.end()
+    }
+
+    /// Returns whether the prefix sums are currently cached.
+    ///
+    pub fn is_cached(&self) -> bool {
+        self.cache.get().is_some()
+    }
+
+    /// Returns the sum of the elements from index 0 to `idx` inclusive. This
+    /// is `O(1)` when the cache is built, and builds it otherwise.
+    ///
+    pub fn prefix_sum(&self, idx: usize) -> T {
+        self.sums()[idx]
+    }
+
+    /// Returns the total prefix sum of all the elements.
+    ///
+    pub fn total(&self) -> T {
+        self.sums()[self.fw.end()]
+    }
+
+    /// Returns the sum of elements from `idx_i` to `idx_j` inclusive. This
+    /// is `O(1)` when the cache is built, and builds it otherwise.
+    ///
+    pub fn range_sum(&self, idx_i: usize, idx_j: usize) -> T {
+        debug_assert!(idx_i <= idx_j && idx_j <= self.fw.end());
+        let sums = self.sums();
+        if idx_i == 0 {
+            sums[idx_j]
+        } else {
+            sums[idx_j] - sums[idx_i - 1]
+        }
+    }
+
+    /// Return a single element's value. This doesn't use or build the cache.
+    ///
+    pub fn get(&self, idx: usize) -> T {
+        self.fw.get(idx)
+    }
+
+    /// Add `delta` to element with index `idx`, invalidating the cache.
+    ///
+    pub fn add(&mut self, idx: usize, delta: T) {
+        self.cache.take();
+        self.fw.add(idx, delta);
+    }
+
+    /// Subtract `delta` from element with index `idx`, invalidating the
+    /// cache.
+    ///
+    pub fn sub(&mut self, idx: usize, delta: T) {
+        self.cache.take();
+        self.fw.sub(idx, delta);
+    }
+
+    /// Set a single element's value, invalidating the cache.
+    ///
+    pub fn set(&mut self, idx: usize, value: T) {
+        self.cache.take();
+        self.fw.set(idx, value);
+    }
+
+    /// Returns the cached prefix sums, building them first if needed.
+    ///
+    fn sums(&self) -> &[T] {
+        self.cache.get_or_init(|| self.fw.prefix_sums_vec())
+    }
+}
+
+impl<T> From<Fenwick<T>> for CachedFenwick<T> {
+    fn from(fw: Fenwick<T>) -> Self {
+        CachedFenwick { fw, cache: OnceLock::new() }
+    }
+}
+
+/// An immutable tree of precomputed prefix sums, for trees that are built
+/// once and then only queried. Unlike `CachedFenwick`, which builds its
+/// cache lazily and throws it away on every update, all the prefix sums are
+/// computed from the consumed `Fenwick` in `O(n)` time-complexity when this
+/// is created, and there are no methods to update it. Every query then takes
+/// `O(1)`. Only the prefix sums are kept, so the memory used is the same as
+/// the tree's.
+///
+#[derive(Debug, Clone)]
+pub struct FrozenCachedFenwick<T> {
+    sums: Vec<T>,
+}
+
+impl<T> FrozenCachedFenwick<T>
+where
+    T: Add<Output = T> + Sub<Output = T> + AddAssign + SubAssign +
+       PartialOrd + Default + Copy,
+{
+    /// Returns the index of the last element.
+    ///
+    pub fn end(&self) -> usize {
+        self.sums.len() - 1
+    }
+
+    /// Returns the sum of the elements from index 0 to `idx` inclusive.
+    ///
+    pub fn prefix_sum(&self, idx: usize) -> T {
+        self.sums[idx]
+    }
+
+    /// Returns the total prefix sum of all the elements.
+    ///
+    pub fn total(&self) -> T {
+        self.sums[self.end()]
+    }
+
+    /// Returns the sum of elements from `idx_i` to `idx_j` inclusive.
+    ///
+    pub fn range_sum(&self, idx_i: usize, idx_j: usize) -> T {
+        debug_assert!(idx_i <= idx_j && idx_j <= self.end());
+        if idx_i == 0 {
+            self.sums[idx_j]
+        } else {
+            self.sums[idx_j] - self.sums[idx_i - 1]
+        }
+    }
+
+    /// Return a single element's value.
+    ///
+    pub fn get(&self, idx: usize) -> T {
+        self.range_sum(idx, idx)
+    }
+}
+
+impl<T> From<Fenwick<T>> for FrozenCachedFenwick<T>
+where
+    T: Add<Output = T> + Sub<Output = T> + AddAssign + SubAssign +
+       PartialOrd + Default + Copy,
+{
+    fn from(fw: Fenwick<T>) -> Self {
+        FrozenCachedFenwick { sums: fw.prefix_sums_vec() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn invalidation() {
+        let fw     = Fenwick::from_vec(vec![3, 1, 4, 1, 5]);
+        let mut fw = CachedFenwick::from(fw);
+        assert!(!fw.is_cached());
+        assert_eq!(fw.prefix_sum(2), 8);
+        assert!(fw.is_cached());
+        assert_eq!(fw.range_sum(1, 3), 6);
+        assert_eq!(fw.total(), 14);
+
+        fw.add(1, 10);
+        assert!(!fw.is_cached());
+        assert_eq!(fw.prefix_sum(2), 18);
+        assert_eq!(fw.range_sum(2, 4), 10);
+
+        fw.set(4, 0);
+        assert_eq!(fw.total(), 19);
+        fw.sub(0, 3);
+        assert_eq!(fw.prefix_sum(0), 0);
+        assert_eq!(fw.into_inner().prefix_sums_vec()[..5], [0, 11, 15, 16, 16]);
+    }
+
+    #[test]
+    fn frozen_cached() {
+        let fw     = Fenwick::from_vec(vec![3, -1, 4, 1, -5, 9, 2, 6, 5]);
+        let frozen = FrozenCachedFenwick::from(fw.clone());
+        assert_eq!(frozen.end(), fw.end());
+        assert_eq!(frozen.total(), fw.total());
+        for i in 0..=fw.end() {
+            assert_eq!(frozen.prefix_sum(i), fw.prefix_sum(i));
+            assert_eq!(frozen.get(i), fw.get(i));
+            for j in i..=fw.end() {
+                assert_eq!(frozen.range_sum(i, j), fw.range_sum(i, j));
+            }
+        }
+    }
+}
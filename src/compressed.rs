@@ -0,0 +1,110 @@
+//! A frequency tree over an arbitrary set of ordered keys.
+//!
+
+use crate::Fenwick;
+
+/// Counts occurrences of keys drawn from a fixed set of ordered values, such
+/// as large or sparse integers, by compressing the keys to the dense indices
+/// of a `Fenwick<u64>`. The set of keys is given up front; the tree then
+/// counts each key added and answers rank queries in `O(log n)`
+/// time-complexity.
+///
+#[derive(Debug, Clone)]
+pub struct CompressedFenwick<K> {
+    keys : Vec<K>,
+    fw   : Fenwick<u64>,
+}
+
+impl<K> CompressedFenwick<K>
+where
+    K: Ord + Copy,
+{
+    /// Creates an empty tree for the given set of keys. Duplicate keys are
+    /// removed. This function has `O(k log k)` time-complexity.
+    ///
+    pub fn new<I>(keys: I) -> Self
+    where
+        I: IntoIterator<Item = K>,
+    {
+        let mut keys = keys.into_iter().collect::<Vec<_>>();
+        keys.sort_unstable();
+        keys.dedup();
+        let fw = Fenwick::new(keys.len());
+        CompressedFenwick { keys, fw }
+    }
+
+    /// Returns the distinct keys in increasing order. Key `keys()[i]` is
+    /// counted at index `i` of the underlying tree.
+    ///
+    pub fn keys(&self) -> &[K] {
+        &self.keys
+    }
+
+    /// Returns the underlying tree of counts.
+    ///
+    pub fn tree(&self) -> &Fenwick<u64> {
+        &self.fw
+    }
+
+    /// Counts one more occurrence of `key`.
+    /// NOTE: Panics if `key` isn't one of the keys the tree was created with.
+    ///
+    pub fn add_key(&mut self, key: K) {
+        let idx = self.keys.binary_search(&key)
+                           .unwrap_or_else(|_| panic!("Unknown key."));
+        self.fw.add(idx, 1);
+    }
+
+    /// Returns the number of occurrences added with keys `<= key`. `key`
+    /// needn't be one of the keys the tree was created with.
+    ///
+    pub fn rank(&self, key: K) -> usize {
+        match self.keys.partition_point(|&k| k <= key) {
+            0   => 0,
+            idx => self.fw.prefix_sum(idx - 1) as usize,
+        }
+    }
+
+    /// Returns the number of occurrences added in total.
+    ///
+    pub fn len(&self) -> usize {
+        self.fw.total() as usize
+    }
+
+    /// Returns whether no occurrences have been added.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn ranks() {
+        let mut cf = CompressedFenwick::new([1_000_000, -5, 42, 7, 42, 10_000]);
+        assert_eq!(cf.keys(), [-5, 7, 42, 10_000, 1_000_000]);
+        assert!(cf.is_empty());
+        
+        for key in [42, 7, 42, 1_000_000, 42, -5] {
+            cf.add_key(key);
+        }
+        assert_eq!(cf.len(), 6);
+        assert_eq!(cf.rank(-6), 0);
+        assert_eq!(cf.rank(-5), 1);
+        assert_eq!(cf.rank(41), 2);
+        assert_eq!(cf.rank(42), 5);
+        assert_eq!(cf.rank(10_000), 5);
+        assert_eq!(cf.rank(i32::MAX), 6);
+        assert_eq!(cf.tree().get(2), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unknown key")]
+    fn unknown_key() {
+        let mut cf = CompressedFenwick::new([1, 2, 3]);
+        cf.add_key(4);
+    }
+}
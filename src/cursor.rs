@@ -0,0 +1,82 @@
+//! A cursor for answering rank queries with increasing values.
+//!
+
+use std::ops::Add;
+use std::ops::Sub;
+use std::ops::AddAssign;
+use std::ops::SubAssign;
+use std::cmp::PartialOrd;
+
+use crate::Fenwick;
+
+/// Answers a series of `.min_rank_query()` style queries whose values never
+/// decrease, produced by `.rank_cursor()`. The cursor remembers the index
+/// and prefix sum it stopped at and scans forward from there, so `k` queries
+/// over a tree of `n` elements take `O(n + k)` time-complexity in total,
+/// rather than `O(k log n)`. This pays off when the queries are dense.
+///
+/// The cursor borrows the tree, so the tree can't be modified while the
+/// cursor is in use.
+///
+#[derive(Debug, Clone)]
+pub struct RankCursor<'a, T> {
+    fw  : &'a Fenwick<T>,
+    idx : usize,
+    sum : T,
+}
+
+impl<'a, T> RankCursor<'a, T>
+where
+    T: Add<Output = T> + Sub<Output = T> + AddAssign + SubAssign +
+       PartialOrd + Default + Copy,
+{
+    /// Creates a cursor positioned at the first element.
+    ///
+    pub(crate) fn new(fw: &'a Fenwick<T>) -> Self {
+        RankCursor { fw, idx: 0, sum: fw.get(0) }
+    }
+
+    /// Find the smallest index with `.prefix_sum(index) >= value`, advancing
+    /// the cursor to it, or return `None` if the total is less than `value`.
+    /// `value` must be at least the value of the previous query.
+    /// NOTE: Requires all values are non-negative.
+    ///
+    pub fn seek(&mut self, value: T) -> Option<usize> {
+        let end = self.fw.end();
+        while self.sum < value {
+            if self.idx == end {
+                return None;
+            }
+            self.idx += 1;
+            self.sum += self.fw.node_value(self.idx);
+        }
+        Some(self.idx)
+    }
+
+    /// Returns the index the cursor is at.
+    ///
+    pub fn position(&self) -> usize {
+        self.idx
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn seek() {
+        let fw = Fenwick::from_vec(vec![2_u32, 0, 3, 1, 0, 4, 0, 0, 5]);
+        let mut cursor = fw.rank_cursor();
+        for value in 0..=15 {
+            assert_eq!(cursor.seek(value), Some(fw.min_rank_query(value)));
+        }
+        assert_eq!(cursor.position(), 8);
+        assert_eq!(cursor.seek(16), None);
+
+        let mut cursor = fw.rank_cursor();
+        assert_eq!(cursor.seek(6), Some(3));
+        assert_eq!(cursor.seek(6), Some(3));
+        assert_eq!(cursor.seek(7), Some(5));
+    }
+}
@@ -0,0 +1,61 @@
+//! Errors reported by the fallible Fenwick Tree operations.
+//!
+
+use std::error::Error;
+use std::fmt;
+
+/// The error type returned by the checked and `try_` methods.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FenwickError {
+    /// An index was past the last element of the tree, `end`.
+    IndexOutOfBounds { idx: usize, end: usize },
+    /// Updating the element at `idx` would overflow the element type.
+    Overflow { idx: usize },
+    /// An index couldn't be converted to `usize`.
+    InvalidIndex,
+    /// A range's `start` was after its `end`.
+    InvalidRange { start: usize, end: usize },
+    /// Bytes passed to `Fenwick::from_bytes()` weren't a valid encoding.
+    InvalidEncoding,
+    /// Row `row` of a matrix had a different length from the first row.
+    RaggedMatrix { row: usize },
+    /// A node array of length `len` couldn't be accepted as a tree.
+    InvalidNodes { len: usize },
+    /// Memory for a tree of `size` elements couldn't be allocated.
+    AllocationFailed { size: usize },
+}
+
+impl fmt::Display for FenwickError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FenwickError::IndexOutOfBounds { idx, end } => {
+                write!(f, "index {} is out of bounds (the last index is {})",
+                       idx, end)
+            },
+            FenwickError::Overflow { idx } => {
+                write!(f, "arithmetic overflow updating index {}", idx)
+            },
+            FenwickError::InvalidIndex => {
+                write!(f, "index can't be converted to usize")
+            },
+            FenwickError::InvalidRange { start, end } => {
+                write!(f, "range start {} is after its end {}", start, end)
+            },
+            FenwickError::InvalidEncoding => {
+                write!(f, "bytes aren't a valid encoding of a tree")
+            },
+            FenwickError::RaggedMatrix { row } => {
+                write!(f, "matrix row {} differs in length from row 0", row)
+            },
+            FenwickError::InvalidNodes { len } => {
+                write!(f, "the {} nodes given don't form a valid tree", len)
+            },
+            FenwickError::AllocationFailed { size } => {
+                write!(f, "couldn't allocate a tree of {} elements", size)
+            },
+        }
+    }
+}
+
+impl Error for FenwickError {}
@@ -0,0 +1,141 @@
+//! A read-only wrapper that removes the mutating methods of a Fenwick Tree.
+//!
+
+use std::ops::Add;
+use std::ops::Sub;
+use std::ops::AddAssign;
+use std::ops::SubAssign;
+use std::cmp::PartialOrd;
+use std::cmp::Ordering;
+
+use crate::{Fenwick, FenwickIter};
+
+/// A `Fenwick` tree that can only be queried, produced by `.freeze()`. None
+/// of the mutating methods are available, so handing out a `FrozenFenwick`
+/// rules out accidental updates at compile time:
+///
+/// ```compile_fail
+/// use fenwick::Fenwick;
+///
+/// let mut frozen = Fenwick::<i32>::new(8).freeze();
+/// frozen.add(0, 1);
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct FrozenFenwick<T> {
+    fw: Fenwick<T>,
+}
+
+impl<T> FrozenFenwick<T>
+where
+    T: Add<Output = T> + Sub<Output = T> + AddAssign + SubAssign +
+       PartialOrd + Default + Copy,
+{
+    /// Wraps the given tree.
+    ///
+    pub(crate) fn new(fw: Fenwick<T>) -> Self {
+        FrozenFenwick { fw }
+    }
+
+    /// Returns a non-consuming iterator over the tree's elements.
+    ///
+    pub fn iter(&self) -> FenwickIter<'_, T> {
+        self.fw.iter()
+    }
+
+    /// Returns the sum of the elements from index 0 to `idx` inclusive.
+    ///
+    pub fn prefix_sum(&self, idx: usize) -> T {
+        self.fw.prefix_sum(idx)
+    }
+
+    /// Returns the total prefix sum of all the elements.
+    ///
+    pub fn total(&self) -> T {
+        self.fw.total()
+    }
+
+    /// Returns the index of the last element.
+    ///
+    pub fn end(&self) -> usize {
+        self.fw.end()
+    }
+
+    /// Return a single element's value.
+    ///
+    pub fn get(&self, idx: usize) -> T {
+        self.fw.get(idx)
+    }
+
+    /// Returns the sum of elements from `idx_i` to `idx_j` inclusive.
+    ///
+    pub fn range_sum(&self, idx_i: usize, idx_j: usize) -> T {
+        self.fw.range_sum(idx_i, idx_j)
+    }
+
+    /// Returns the sum of elements from `idx_i` to `idx_j` non-inclusive.
+    ///
+    pub fn range_sum2(&self, idx_i: usize, idx_j: usize) -> T {
+        self.fw.range_sum2(idx_i, idx_j)
+    }
+
+    /// Returns the sum of elements over the half-open range `[start, end)`.
+    ///
+    pub fn range_sum_exclusive(&self, start: usize, end: usize) -> T {
+        self.fw.range_sum_exclusive(start, end)
+    }
+
+    /// Find the largest index with `.prefix_sum(index) <= value`.
+    /// NOTE: Requires all values are non-negative.
+    ///
+    pub fn rank_query(&self, value: T) -> usize {
+        self.fw.rank_query(value)
+    }
+
+    /// Find the smallest index with `.prefix_sum(index) >= value`.
+    /// NOTE: Requires all values are non-negative.
+    ///
+    pub fn min_rank_query(&self, value: T) -> usize {
+        self.fw.min_rank_query(value)
+    }
+
+    /// Binary searches the prefix sums with a comparator function.
+    /// NOTE: Requires all values are non-negative.
+    ///
+    pub fn binary_search_by<F>(&self, f: F) -> Result<usize, usize>
+    where
+        F: Fn(T) -> Ordering,
+    {
+        self.fw.binary_search_by(f)
+    }
+
+    /// Returns the element values as a vector.
+    ///
+    pub fn to_difference_array(&self) -> Vec<T> {
+        self.fw.to_difference_array()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn frozen_queries() {
+        let fw     = Fenwick::from_vec(vec![1, 1, 3, 1, 1]);
+        let frozen = fw.clone().freeze();
+        
+        assert_eq!(frozen.end(), fw.end());
+        assert_eq!(frozen.total(), 7);
+        assert_eq!(frozen.get(2), 3);
+        assert_eq!(frozen.prefix_sum(3), 6);
+        assert_eq!(frozen.range_sum(1, 3), 5);
+        assert_eq!(frozen.range_sum2(1, 3), 4);
+        assert_eq!(frozen.range_sum_exclusive(1, 5), 6);
+        assert_eq!(frozen.rank_query(5), 2);
+        assert_eq!(frozen.min_rank_query(6), 3);
+        assert_eq!(frozen.binary_search_by(|s| s.cmp(&5)), Ok(2));
+        assert_eq!(frozen.to_difference_array()[..5], [1, 1, 3, 1, 1]);
+        assert_eq!(frozen.iter().next(), Some(1));
+    }
+}
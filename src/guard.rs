@@ -0,0 +1,95 @@
+//! A guard for read-modify-write access to a single element.
+//!
+
+use std::ops::Add;
+use std::ops::Sub;
+use std::ops::AddAssign;
+use std::ops::SubAssign;
+use std::ops::Deref;
+use std::ops::DerefMut;
+use std::cmp::PartialOrd;
+
+use crate::Fenwick;
+
+/// A mutable handle on one element of a tree, produced by `.element_mut()`.
+/// The guard holds a copy of the element's value, which can be read and
+/// modified through `Deref` and `DerefMut`. When the guard is dropped, the
+/// new value is written back to the tree with `.set()`, updating the prefix
+/// sums.
+///
+#[derive(Debug)]
+pub struct ElementGuard<'a, T>
+where
+    T: Add<Output = T> + Sub<Output = T> + AddAssign + SubAssign +
+       PartialOrd + Default + Copy,
+{
+    fw    : &'a mut Fenwick<T>,
+    idx   : usize,
+    value : T,
+}
+
+impl<'a, T> ElementGuard<'a, T>
+where
+    T: Add<Output = T> + Sub<Output = T> + AddAssign + SubAssign +
+       PartialOrd + Default + Copy,
+{
+    /// Creates a guard holding the current value of element `idx`.
+    ///
+    pub(crate) fn new(fw: &'a mut Fenwick<T>, idx: usize) -> Self {
+        let value = fw.get(idx);
+        ElementGuard { fw, idx, value }
+    }
+}
+
+impl<'a, T> Deref for ElementGuard<'a, T>
+where
+    T: Add<Output = T> + Sub<Output = T> + AddAssign + SubAssign +
+       PartialOrd + Default + Copy,
+{
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<'a, T> DerefMut for ElementGuard<'a, T>
+where
+    T: Add<Output = T> + Sub<Output = T> + AddAssign + SubAssign +
+       PartialOrd + Default + Copy,
+{
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+impl<'a, T> Drop for ElementGuard<'a, T>
+where
+    T: Add<Output = T> + Sub<Output = T> + AddAssign + SubAssign +
+       PartialOrd + Default + Copy,
+{
+    fn drop(&mut self) {
+        self.fw.set(self.idx, self.value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn element_mut() {
+        let mut fw = Fenwick::from_vec(vec![3, 1, 4, 1, 5]);
+        {
+            let mut g = fw.element_mut(2);
+            assert_eq!(*g, 4);
+            *g += 5;
+            *g -= 2;
+        }
+        assert_eq!(fw.get(2), 7);
+        assert_eq!(fw.prefix_sums_vec()[..5], [3, 4, 11, 12, 17]);
+
+        *fw.element_mut(0) = 0;
+        assert_eq!(fw.total(), 14);
+    }
+}
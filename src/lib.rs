@@ -11,42 +11,453 @@
 //! Wikipedia article: <https://en.wikipedia.org/wiki/Fenwick_tree>
 //!
 
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::convert::TryInto;
 use std::iter::{FromIterator, IntoIterator};
+use std::mem::size_of;
 use std::ops::Add;
 use std::ops::Sub;
+use std::ops::Mul;
 use std::ops::AddAssign;
 use std::ops::SubAssign;
-use std::cmp::Ord;
+use std::ops::RangeInclusive;
+use std::cmp::PartialOrd;
+use std::cmp::Ordering;
 
 macro_rules! lsb {
     ($i:expr) => { $i & $i.wrapping_neg() }
 }
 
+/// With the `debug-invariants` feature, checks the tree's invariants after a
+/// mutation, panicking with a description of any violated. Without the
+/// feature, this expands to nothing.
+///
+macro_rules! assert_invariants {
+    ($fw:expr) => {
+        #[cfg(feature = "debug-invariants")]
+        $fw.check_invariants();
+    }
+}
+
+mod cached;
+mod compressed;
+mod cursor;
+mod error;
+mod frozen;
+mod guard;
+mod modular;
+mod nd;
+#[cfg(feature = "num-traits")]
+mod num;
+mod one_based;
+#[cfg(feature = "rayon")]
+mod par;
+mod product;
+mod signed;
+mod sparse;
+mod view;
+mod window;
+
+pub use crate::cached::{CachedFenwick, FrozenCachedFenwick};
+pub use crate::compressed::CompressedFenwick;
+pub use crate::cursor::RankCursor;
+pub use crate::error::FenwickError;
+pub use crate::frozen::FrozenFenwick;
+pub use crate::guard::ElementGuard;
+pub use crate::modular::FenwickMod;
+pub use crate::nd::{Fenwick2D, FenwickND};
+pub use crate::one_based::FenwickOneBased;
+pub use crate::product::FenwickProduct;
+pub use crate::signed::SignedRankFenwick;
+pub use crate::sparse::SparseFenwick;
+pub use crate::view::FenwickView;
+pub use crate::window::SlidingWindow;
+
+/// Returns the size of the tree built to hold `len` elements: 1 plus the
+/// smallest power of 2 that's at least `len`.
+///
+fn padded_size(len: usize) -> usize {
+    len.next_power_of_two() + 1
+}
+
+/// Returns whether `len` is a valid number of tree nodes, 1 + a power of 2.
+///
+fn is_tree_size(len: usize) -> bool {
+    len >= 2 && (len - 1).is_power_of_two()
+}
+
+/// Adds `value` into `node` while building the tree. With the `checked-build`
+/// feature, a sum that wraps around panics naming the node being built, even
+/// in release builds where Rust's own overflow checks are off. The check
+/// relies only on comparisons, so any element type can be built this way.
+///
+#[cfg(feature = "checked-build")]
+fn build_add<T>(node: T, value: T, idx: usize) -> T
+where
+    T: Add<Output = T> + PartialOrd + Default + Copy,
+{
+    let sum  = node + value;
+    let zero = T::default();
+    if (value > zero && sum < node) || (value < zero && sum > node) {
+        panic!("arithmetic overflow building node {} of the tree", idx);
+    }
+    sum
+}
+
+#[cfg(not(feature = "checked-build"))]
+fn build_add<T>(node: T, value: T, _idx: usize) -> T
+where
+    T: Add<Output = T> + PartialOrd + Default + Copy,
+{
+    node + value
+}
+
+/// Selects which index `.rank_query_with()` returns when several consecutive
+/// indices share the matching prefix sum, as happens over runs of zeros.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tie {
+    /// The lowest index with the matching prefix sum.
+    First,
+    /// The highest index with the matching prefix sum.
+    Last,
+}
+
+/// Selects how a tree created by `Fenwick::new_mode()` is used. Trees from
+/// every other constructor are in `Mode::PointUpdateRangeQuery` mode.
+///
+/// Keeping both uses in one type means one set of methods and conversions,
+/// at the cost of checking the mode at runtime: calling `.range_add()` or
+/// `.point_get()` in the wrong mode panics rather than failing to compile,
+/// and the classic methods like `.add()` and `.prefix_sum()` work directly
+/// on the difference array in `Mode::RangeUpdatePointQuery` mode.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// The classic use: update single elements and query prefix sums.
+    PointUpdateRangeQuery,
+    /// Add to ranges of elements with `.range_add()` and read single
+    /// elements with `.point_get()`. The tree holds the differences between
+    /// adjacent elements, so each element is a prefix sum of the tree.
+    RangeUpdatePointQuery,
+}
+
 /// Represents a prefix sum array with `O(log n)` update operations.
 ///
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Fenwick<T> {
     data: Vec<T>,
     size: usize,
+    mode: Mode,
+}
+
+impl<T> Fenwick<T>
+where
+    T: Add<Output = T> + Sub<Output = T> + AddAssign + SubAssign +
+       PartialOrd + Copy,
+{
+    /// Creates a new tree for an element type without a `Default`, using
+    /// `zero()` in its place to fill the elements. The methods in this `impl`
+    /// block - the prefix sums and point updates - don't need `Default`, so
+    /// they can be used on such a tree. The size is adjusted as in `.new()`.
+    ///
+    pub fn with_default_fn<F>(size: usize, zero: F) -> Self
+    where
+        F: Fn() -> T,
+    {
+        // Ensure size is 1 plus a power of 2.
+        let size = padded_size(size);
+        let data = (0..size).map(|_| zero()).collect();
+        
+        Fenwick { data, size, mode: Mode::PointUpdateRangeQuery }
+    }
+
+    /// Returns the sum of the first `idx` elements (indices 0 to `idx`)
+    /// Equivalent to `.range_sum(0, idx)`. Range inclusive, [0..idx].
+    ///
+    #[inline]
+    pub fn prefix_sum(&self, idx: usize) -> T {
+        debug_assert!(idx <= self.end());
+        self.prefix_sum_raw(idx)
+    }
+
+    /// Returns the total prefix sum of all the elements.
+    ///
+    #[inline]
+    pub fn total(&self) -> T {
+        self.data[self.end()] + self.data[0]
+    }
+
+    /// Returns the index of the last element. This can be used as a parameter
+    /// to `.range_sum()` or other methods.
+    ///
+    #[inline]
+    pub fn end(&self) -> usize {
+        self.size - 1
+    }
+
+    /// Add `delta` to element with index `idx` (zero-based).
+    ///
+    #[inline]
+    pub fn add(&mut self, idx: usize, delta: T) {
+        debug_assert!(idx <= self.end());
+        self.add_raw(idx, delta);
+        assert_invariants!(self);
+    }
+
+    /// Subtract `delta` from element with index `idx`.
+    /// 
+    #[inline]
+    pub fn sub(&mut self, idx: usize, delta: T) {
+        debug_assert!(idx <= self.end());
+        self.sub_raw(idx, delta);
+        assert_invariants!(self);
+    }
+
+    /// Add `delta` to element with index `idx` as `.add()` does, converting
+    /// it into the tree's element type first. This saves casts when deltas
+    /// arrive as a narrower type, such as `i32` deltas for an `i64` tree.
+    ///
+    pub fn add_with<D: Into<T>>(&mut self, idx: usize, delta: D) {
+        self.add(idx, delta.into());
+    }
+
+    /// The core of `.prefix_sum()`, without the bounds assertion. Loops that
+    /// have already checked their indices call this directly. Slicing the
+    /// nodes up to `idx` first checks the bounds once, after which the
+    /// compiler can see that every node visited is in range, since `i` only
+    /// decreases from `idx`.
+    ///
+    #[inline]
+    fn prefix_sum_raw(&self, idx: usize) -> T {
+        let data    = &self.data[..=idx];
+        let mut sum = data[0];
+        let mut i   = idx;
+        while i != 0 { 
+            sum += data[i];
+            i   -= lsb!(i);
+        }
+        sum
+    }
+
+    /// The core of `.add()`, without the bounds assertion or invariant
+    /// checks. Batch updates that have already checked their indices call
+    /// this directly and check the invariants once at the end. The loop is
+    /// bounded by the length of the node vector rather than `.size`, which
+    /// lets the compiler drop the bounds check on each node.
+    ///
+    #[inline]
+    fn add_raw(&mut self, idx: usize, delta: T) {
+        if idx == 0 {
+            self.data[0] += delta;
+        } else {
+            let data  = &mut self.data[..];
+            let mut i = idx;
+            while i < data.len() {
+                data[i] += delta;
+                i       += lsb!(i);
+            }
+        }
+    }
+
+    /// The core of `.sub()`, without the bounds assertion or invariant
+    /// checks. The loop is bounded as in `.add_raw()`.
+    ///
+    #[inline]
+    fn sub_raw(&mut self, idx: usize, delta: T) {
+        if idx == 0 {
+            self.data[0] -= delta;
+        } else {
+            let data  = &mut self.data[..];
+            let mut i = idx;
+            while i < data.len() {
+                data[i] -= delta;
+                i       += lsb!(i);
+            }
+        }
+    }
+
+    /// Panics if the tree's invariants are violated: the node array must
+    /// hold exactly `.end() + 1` nodes, its size must be 1 + a power of 2,
+    /// and no node may be NaN (incomparable with itself). Unlike `.validate()`,
+    /// nodes aren't round-tripped through their element values, as rounding
+    /// would make float trees fail that check after ordinary updates.
+    ///
+    #[cfg(feature = "debug-invariants")]
+    fn check_invariants(&self) {
+        assert!(self.data.len() == self.size,
+                "Fenwick invariant violated: {} nodes for size {}.",
+                self.data.len(), self.size);
+        assert!(is_tree_size(self.size),
+                "Fenwick invariant violated: size {} isn't 1 + a power of 2.",
+                self.size);
+        let is_nan = |node: &T| node.partial_cmp(node).is_none();
+        if let Some(i) = self.data.iter().position(is_nan) {
+            panic!("Fenwick invariant violated: node {} is NaN.", i);
+        }
+    }
 }
 
 impl<T> Fenwick<T>
 where 
-    T: Add<Output = T> + Sub<Output = T> + AddAssign + SubAssign + Ord + 
-       Default + Copy, 
+    T: Add<Output = T> + Sub<Output = T> + AddAssign + SubAssign + 
+       PartialOrd + Default + Copy, 
 {
     /// Creates a new Fenwick Tree for use in calculating and updating
     /// prefix sums. The size is adjusted to be 1 + a power of 2 if it already
     /// isn't.
     ///
     pub fn new(size: usize) -> Self {
+        Self::new_mode(size, Mode::PointUpdateRangeQuery)
+    }
+
+    /// Creates a new zero-filled tree as `.new()` does, but returns an error
+    /// rather than aborting the process if the memory can't be allocated.
+    /// The padded size is computed without overflowing and the buffer is
+    /// reserved with `Vec::try_reserve_exact()`, so a `size` taken from
+    /// untrusted input fails gracefully. Note that on systems which
+    /// overcommit memory, an allocation may succeed here and still fail
+    /// later when the pages are touched.
+    ///
+    pub fn try_new(size: usize) -> Result<Self, FenwickError> {
+        let padded = size.checked_next_power_of_two()
+                         .and_then(|p| p.checked_add(1))
+                         .ok_or(FenwickError::AllocationFailed { size })?;
+        let mut data = Vec::new();
+        data.try_reserve_exact(padded)
+            .map_err(|_| FenwickError::AllocationFailed { size })?;
+        data.resize(padded, T::default());
+        
+        Ok(Fenwick { data, size: padded, mode: Mode::PointUpdateRangeQuery })
+    }
+
+    /// Creates a new zero-filled tree used in the given `mode`. The size is
+    /// adjusted as in `.new()`.
+    ///
+    pub fn new_mode(size: usize, mode: Mode) -> Self {
         // Ensure size is 1 plus a power of 2.
-        let n_bits = (size as f64).log(2_f64).ceil();
-        let size   = 2_usize.pow(n_bits as u32) + 1_usize;
+        let size = padded_size(size);
         
-        Fenwick { data: vec![T::default(); size], size }
+        Fenwick { data: vec![T::default(); size], size, mode }
     }
-    
+
+    /// Returns the mode the tree is used in.
+    ///
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Add `delta` to every element from `start` to `end`, inclusive. This
+    /// method has `O(log n)` time-complexity.
+    /// NOTE: Panics unless the tree is in `Mode::RangeUpdatePointQuery` mode.
+    ///
+    pub fn range_add(&mut self, start: usize, end: usize, delta: T) {
+        assert_eq!(self.mode, Mode::RangeUpdatePointQuery,
+                   "range_add() requires Mode::RangeUpdatePointQuery.");
+        debug_assert!(start <= end && end <= self.end());
+        self.add(start, delta);
+        if end < self.end() {
+            self.sub(end + 1, delta);
+        }
+    }
+
+    /// Returns the value of the element with index `idx`. This method has
+    /// `O(log n)` time-complexity.
+    /// NOTE: Panics unless the tree is in `Mode::RangeUpdatePointQuery` mode.
+    ///
+    pub fn point_get(&self, idx: usize) -> T {
+        assert_eq!(self.mode, Mode::RangeUpdatePointQuery,
+                   "point_get() requires Mode::RangeUpdatePointQuery.");
+        self.prefix_sum(idx)
+    }
+
+    /// Creates a new tree with every element set to zero (`T::default()`).
+    /// This is the same as `.new()`, but says so explicitly: the elements are
+    /// all initialized, not merely reserved. The size is adjusted as in
+    /// `.new()`.
+    ///
+    /// ```
+    /// use fenwick::Fenwick;
+    ///
+    /// let fw = Fenwick::<i32>::zeros(8);
+    /// assert_eq!(fw.end(), 8);
+    /// assert_eq!(fw.total(), 0);
+    /// ```
+    ///
+    pub fn zeros(size: usize) -> Self {
+        Self::new(size)
+    }
+
+    /// Creates a tree for zero elements, which has the smallest size allowed.
+    /// Like any tree it still has valid indices 0 and 1, each holding zero.
+    ///
+    /// ```
+    /// use fenwick::Fenwick;
+    ///
+    /// let fw = Fenwick::<i32>::empty();
+    /// assert_eq!(fw.end(), 1);
+    /// assert_eq!(fw.total(), 0);
+    /// ```
+    ///
+    pub fn empty() -> Self {
+        Self::new(0)
+    }
+
+    /// Creates a tree of zeros except for element `idx`, which is set to
+    /// `value`. Rather than building the tree from a vector of values, only
+    /// the `O(log n)` nodes on the update path of `idx` are written after
+    /// the zeroed allocation. The size is adjusted as in `.new()`.
+    ///
+    pub fn singleton(size: usize, idx: usize, value: T) -> Self {
+        assert!(idx < size, "Index {} is past the size {}.", idx, size);
+        let mut fw = Self::new(size);
+        fw.add(idx, value);
+        fw
+    }
+
+    /// Creates a new tree holding `size` copies of `value`, equivalent to
+    /// building it from `vec![value; size]`. The size is adjusted as in 
+    /// `.new()` with any extra elements set to zero. Since all the values are 
+    /// equal, each node is a multiple of `value` computed from a table of 
+    /// doublings, so no general build pass is needed. This function has `O(n)`
+    /// time-complexity.
+    ///
+    pub fn repeat(value: T, size: usize) -> Self {
+        let mut fw = Self::new(size);
+        if size == 0 {
+            return fw;
+        }
+        // Positions 1 through size - 1 hold the value; pow[k] is value * 2^k.
+        let last    = size - 1;
+        let n_bits  = (usize::BITS - last.leading_zeros()) as usize;
+        let mut pow = Vec::with_capacity(n_bits);
+        if n_bits > 0 {
+            pow.push(value);
+        }
+        while pow.len() < n_bits {
+            let p = pow[pow.len() - 1];
+            pow.push(p + p);
+        }
+        fw.data[0] = value;
+        
+        for i in 1..fw.size {
+            let lo    = i - lsb!(i);
+            let count = if i <= last { lsb!(i) } 
+                        else         { last.saturating_sub(lo) };
+            if count == lsb!(i) {
+                fw.data[i] = pow[i.trailing_zeros() as usize];
+            } else {
+                for (k, &p) in pow.iter().enumerate() {
+                    if count & (1 << k) != 0 {
+                        fw.data[i] += p;
+                    }
+                }
+            }
+        }
+        fw
+    }
+
     /// Creates a new Fenwick instance from the provided slice. The data in 
     /// the slice itself doesn't need to be in accumulated prefix sum form.
     /// It should just be a slice of unsummed values. This function has `O(n)`
@@ -54,22 +465,15 @@ where
     ///
     fn from_slice(slice: &[T]) -> Self {
         // Ensure size is 1 plus a power of 2.
-        let n_bits = (slice.len() as f64).log(2_f64).ceil();
-        let size   = 2_usize.pow(n_bits as u32) + 1_usize;
+        let size = padded_size(slice.len());
         
         let mut data = Vec::with_capacity(size);
         
         data.extend_from_slice(slice);        
         data.resize(size, T::default());
         
-        for i in 1..size {
-            let j = i + lsb!(i);
-            if j < size {
-                let d = data[i];
-                data[j] += d;
-            }
-        }
-        Fenwick { data, size }
+        Self::build_slice(&mut data);
+        Fenwick { data, size, mode: Mode::PointUpdateRangeQuery }
     }
     
     /// Creates a new Fenwick instance from the provided vector. The data in 
@@ -80,87 +484,234 @@ where
     ///
     fn from_vec(vec: Vec<T>) -> Self {
         // Ensure size is 1 plus a power of 2.
-        let n_bits = (vec.len() as f64).log(2_f64).ceil();
-        let size   = 2_usize.pow(n_bits as u32) + 1_usize;
+        let size = padded_size(vec.len());
 
         let mut data = vec;
         
         data.resize(size, T::default());
         
-        for i in 1..size {
-            let j = i + lsb!(i);
-            if j < size {
-                let d = data[i];
-                data[j] += d;
+        Self::build_slice(&mut data);
+        Fenwick { data, size, mode: Mode::PointUpdateRangeQuery }
+    }
+
+    /// Creates a new Fenwick instance from the provided vector as
+    /// `.from_vec()` does, except that a vector whose length is already a
+    /// valid tree size, 1 + a power of 2, is built as is rather than padded.
+    /// Rebuilding the values of an existing tree this way keeps its size.
+    ///
+    fn from_vec_unpadded(vec: Vec<T>) -> Self {
+        if !is_tree_size(vec.len()) {
+            return Self::from_vec(vec);
+        }
+        let size     = vec.len();
+        let mut data = vec;
+        
+        Self::build_slice(&mut data);
+        Fenwick { data, size, mode: Mode::PointUpdateRangeQuery }
+    }
+
+    /// Creates a tree used in `mode` whose elements hold `values`, as
+    /// `.element_values()` returns them. `values` must already hold a valid
+    /// tree size, 1 + a power of 2; in `Mode::RangeUpdatePointQuery` mode
+    /// they're differenced before the tree is built.
+    ///
+    fn from_element_values(values: Vec<T>, mode: Mode) -> Self {
+        debug_assert!(is_tree_size(values.len()));
+        let size     = values.len();
+        let mut data = values;
+        
+        if mode == Mode::RangeUpdatePointQuery {
+            for i in (1..size).rev() {
+                let prev = data[i - 1];
+                data[i] -= prev;
             }
         }
-        Fenwick { data, size }
+        Self::build_slice(&mut data);
+        Fenwick { data, size, mode }
+    }
+
+    /// Creates a new Fenwick instance from `sums`, an array of cumulative
+    /// prefix sums rather than element values: `sums[i]` becomes
+    /// `.prefix_sum(i)`. The sums are differenced into element values and
+    /// built into a tree, in `O(n)` time-complexity. The size is adjusted as
+    /// in `.new()`, with any extra elements set to zero.
+    ///
+    pub fn from_prefix_sums(sums: &[T]) -> Self {
+        let mut values = Vec::with_capacity(padded_size(sums.len()));
+        let mut prev   = T::default();
+        for &sum in sums {
+            values.push(sum - prev);
+            prev = sum;
+        }
+        Self::from_vec(values)
+    }
+
+    /// Creates a tree directly from `nodes`, an already built node array such
+    /// as one saved from another tree. The array must hold 1 + a power of 2
+    /// nodes (at least 2), and decumulating it into element values and
+    /// rebuilding must reproduce it exactly; otherwise an error is returned.
+    /// For integer types every array of a valid length passes the round trip,
+    /// so this chiefly catches truncated arrays and, for floats, corrupt
+    /// values like NaN. This function has `O(n)` time-complexity.
+    ///
+    pub fn try_from_raw_nodes(nodes: Vec<T>) -> Result<Self, FenwickError> {
+        let len = nodes.len();
+        if !is_tree_size(len) {
+            return Err(FenwickError::InvalidNodes { len });
+        }
+        let mode = Mode::PointUpdateRangeQuery;
+        let fw   = Fenwick { data: nodes, size: len, mode };
+        if fw.validate() {
+            Ok(fw)
+        } else {
+            Err(FenwickError::InvalidNodes { len })
+        }
+    }
+
+    /// Creates a new Fenwick instance from values that may or may not be
+    /// owned. An owned vector is built into the tree in place as with
+    /// `Fenwick::from(vec)`, while borrowed values are copied as with
+    /// `Fenwick::from(slice)`. This function has `O(n)` time-complexity.
+    ///
+    pub fn from_cow(values: Cow<'_, [T]>) -> Self {
+        match values {
+            Cow::Owned(vec)      => Self::from_vec(vec),
+            Cow::Borrowed(slice) => Self::from_slice(slice),
+        }
+    }
+
+    /// Creates a new Fenwick instance from an iterator of fallible values,
+    /// such as values parsed from lines of text. The first `Err` found is
+    /// returned without consuming the rest; otherwise the values are built
+    /// into a tree as with `.from_iter()`. This function has `O(n)`
+    /// time-complexity.
+    ///
+    pub fn try_from_iter<I, E>(iter: I) -> Result<Self, E>
+    where
+        I: IntoIterator<Item = Result<T, E>>,
+    {
+        let values = iter.into_iter().collect::<Result<Vec<T>, E>>()?;
+        Ok(Self::from_vec(values))
     }
 
     /// Returns a non-consuming iterator over the Fenwick Tree. The iterator 
-    /// will return the prefix sum of each element in the tree. The iterator 
-    /// iterates over elements with `O(log(n))` time-complexity each.
+    /// will return the value of each element in the tree (not its prefix 
+    /// sum), stopping before the last element, `.end()`. The iterator 
+    /// iterates over elements with `O(log(n))` time-complexity each. Prefer
+    /// `.values()` for all the element values and `.prefix_sums()` for the
+    /// prefix sums.
     /// 
-    pub fn iter(&self) -> FenwickIter<T> {
+    pub fn iter(&self) -> FenwickIter<'_, T> {
         self.into_iter()
     }
 
-    /// Returns the sum of the first `idx` elements (indices 0 to `idx`)
-    /// Equivalent to `.range_sum(0, idx)`. Range inclusive, [0..idx].
+    /// Returns the individual element values, including the last, as a
+    /// vector. This is the inverse of building a tree from a vector, so
+    /// `Fenwick::from(fw.decumulate())` holds the same element values as
+    /// `fw`, though with more padding after them. The nodes are copied
+    /// and converted back in place in `O(n)` time-complexity; use
+    /// `.values()` to visit them without allocating or `.into_values()` to
+    /// reuse the tree's own storage.
     ///
-    pub fn prefix_sum(&self, idx: usize) -> T {
-        debug_assert!(idx <= self.end());
-        let mut sum = self.data[0];
-        let mut i   = idx;
-        while i != 0 { 
-            sum += self.data[i];
-            i   -= lsb!(i);
-        }
-        sum
+    pub fn decumulate(&self) -> Vec<T> {
+        let mut values = self.data.clone();
+        Self::decumulate_slice(&mut values);
+        values
     }
-    
-    /// Returns the total prefix sum of all the elements.
+
+    /// Returns a non-consuming iterator over the value of every element,
+    /// including the last. The values are recovered from the tree nodes in
+    /// amortized `O(1)` time-complexity each.
     ///
-    pub fn total(&self) -> T {
-        self.data[self.end()] + self.data[0]
+    pub fn values(&self) -> impl Iterator<Item = T> + '_ {
+        (0..self.size).map(move |i| self.node_value(i))
     }
-    
-    /// Returns the index of the last element. This can be used as a parameter
-    /// to `.range_sum()` or other methods.
+
+    /// Returns an iterator over the tree's internal nodes, for inspecting
+    /// its structure. Each item is `(node_index, node_value, covered)`, where
+    /// the node holds the sum of the elements in the range `covered`. Node 0
+    /// holds element 0 alone, and every other node `i` covers
+    /// `i - lsb(i) + 1..=i`, where `lsb(i)` is the lowest set bit of `i`.
     ///
-    pub fn end(&self) -> usize {
-        self.size - 1
+    pub fn iter_nodes(&self) 
+        -> impl Iterator<Item = (usize, T, RangeInclusive<usize>)> + '_ 
+    {
+        self.data.iter().enumerate().map(|(i, &node)| {
+            let first = if i == 0 { 0 } else { i - lsb!(i) + 1 };
+            (i, node, first..=i)
+        })
     }
-    
-    /// Add `delta` to element with index `idx` (zero-based).
+
+    /// Returns an iterator over the values of the elements from `start` up
+    /// to, but not including, `end`. Each value is recovered from its node
+    /// as in `.values()`, for `O(end - start + log n)` time-complexity
+    /// overall.
     ///
-    pub fn add(&mut self, idx: usize, delta: T) {
-        debug_assert!(idx <= self.end());
-        if idx == 0 {
-            self.data[0] += delta;
-        } else {
-            let mut i = idx;
-            while i < self.size {
-                self.data[i] += delta;
-                i += lsb!(i);
-            }
-        }
+    pub fn element_range(&self, start: usize, end: usize) 
+        -> impl Iterator<Item = T> + '_ 
+    {
+        debug_assert!(start <= end && end <= self.size);
+        (start..end).map(move |i| self.node_value(i))
     }
-    
-    /// Subtract `delta` from element with index `idx`.
-    /// 
-    pub fn sub(&mut self, idx: usize, delta: T) {
-        debug_assert!(idx <= self.end());
-        if idx == 0 {
-            self.data[0] -= delta;
+
+    /// Returns a non-consuming iterator over the prefix sum at every index,
+    /// taking amortized `O(1)` time-complexity each. Use `.prefix_sums_vec()`
+    /// to collect them into a vector.
+    ///
+    pub fn prefix_sums(&self) -> impl Iterator<Item = T> + '_ {
+        self.values().scan(T::default(), |sum, value| {
+            *sum += value;
+            Some(*sum)
+        })
+    }
+
+    /// Returns an iterator over the prefix sums from index `start` to `end`,
+    /// inclusive. The first prefix sum takes `O(log n)` time-complexity, and
+    /// each one after it is found by adding the next element's value, for
+    /// `O(end - start + log n)` time-complexity overall.
+    ///
+    pub fn prefix_range(&self, start: usize, end: usize) 
+        -> impl Iterator<Item = T> + '_ 
+    {
+        debug_assert!(start <= end && end <= self.end());
+        let first = self.prefix_sum(start);
+        std::iter::once(first).chain(
+            (start + 1..=end).scan(first, move |sum, i| {
+                *sum += self.node_value(i);
+                Some(*sum)
+            }))
+    }
+
+    /// Returns a consuming iterator over the element values (not the prefix
+    /// sums), including the last element. The values are recovered in place
+    /// in `O(n)` time-complexity overall, without copying the tree.
+    ///
+    pub fn into_values(mut self) -> impl Iterator<Item = T> {
+        Self::decumulate_slice(&mut self.data);
+        self.data.into_iter()
+    }
+
+    /// Returns the sum of the elements from index 0 to `idx` inclusive, or
+    /// `None` if `idx` is past the last element. Unlike `.prefix_sum()`, this
+    /// is safe to call with untrusted indices in any build.
+    ///
+    pub fn prefix_sum_checked(&self, idx: usize) -> Option<T> {
+        if idx < self.size {
+            Some(self.prefix_sum_raw(idx))
         } else {
-            let mut i = idx;
-            while i < self.size {
-                self.data[i] -= delta;
-                i += lsb!(i);
-            }
+            None
         }
     }
+
+    /// Returns the `n`th prefix sum, the sum of elements 0 to `n` inclusive,
+    /// or `None` if `n` is past the last element. This is
+    /// `.prefix_sum_checked()` under a name that reads naturally alongside
+    /// iterator code such as `.prefix_sums().nth(n)`, but takes `O(log n)`
+    /// time-complexity rather than `O(n)`.
+    ///
+    pub fn nth_prefix(&self, n: usize) -> Option<T> {
+        self.prefix_sum_checked(n)
+    }
     
     /// Set (as opposed to adjust) a single element's value.
     ///
@@ -173,37 +724,149 @@ where
             self.sub(idx, cur_val - value);
         }
     }
-    
-    /// Return a single element's value.
+
+    /// Returns a guard for modifying the element with index `idx` in place,
+    /// e.g. `*fw.element_mut(idx) += 5`. The new value is written back to
+    /// the tree when the guard is dropped, in `O(log n)` time-complexity.
     ///
-    pub fn get(&self, idx: usize) -> T {
+    pub fn element_mut(&mut self, idx: usize) -> ElementGuard<'_, T> {
         debug_assert!(idx <= self.end());
-        if idx == 0 {
-            self.data[0]
+        ElementGuard::new(self, idx)
+    }
+
+    /// Set a single element's value, or return an error without modifying 
+    /// the tree if `idx` is past the last element. Unlike `.set()`, whose
+    /// bounds assertion is compiled out of release builds, the index is
+    /// checked in all builds, so this suits untrusted input.
+    ///
+    pub fn set_checked(&mut self, idx: usize, value: T) 
+        -> Result<(), FenwickError> 
+    {
+        self.check_index(idx)?;
+        self.set(idx, value);
+        Ok(())
+    }
+
+    /// Return a single element's value.
+    ///
+    #[inline]
+    pub fn get(&self, idx: usize) -> T {
+        debug_assert!(idx <= self.end());
+        if idx == 0 {
+            self.data[0]
         } else {
             self.range_sum(idx, idx)
         }
     }
     
+    /// Return a single element's value, or `None` if `idx` is past the last
+    /// element.
+    ///
+    pub fn get_checked(&self, idx: usize) -> Option<T> {
+        if idx < self.size {
+            Some(self.get(idx))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the sum of the elements from index 0 to `idx` inclusive, for
+    /// an index of any type convertible to `usize`, such as a `u32` read from
+    /// a file. An error is returned if the index can't be converted or is
+    /// past the last element.
+    ///
+    pub fn prefix_sum_at<I>(&self, idx: I) -> Result<T, FenwickError>
+    where
+        I: TryInto<usize>,
+    {
+        let idx = self.to_index(idx)?;
+        Ok(self.prefix_sum(idx))
+    }
+
+    /// Returns a single element's value, for an index of any type
+    /// convertible to `usize`. An error is returned if the index can't be
+    /// converted or is past the last element.
+    ///
+    pub fn get_at<I>(&self, idx: I) -> Result<T, FenwickError>
+    where
+        I: TryInto<usize>,
+    {
+        let idx = self.to_index(idx)?;
+        Ok(self.get(idx))
+    }
+
+    /// Add `delta` to the element at an index of any type convertible to
+    /// `usize`. An error is returned, leaving the tree unchanged, if the
+    /// index can't be converted or is past the last element.
+    ///
+    pub fn add_at<I>(&mut self, idx: I, delta: T) -> Result<(), FenwickError>
+    where
+        I: TryInto<usize>,
+    {
+        let idx = self.to_index(idx)?;
+        self.add(idx, delta);
+        Ok(())
+    }
+
+    /// Set the value of the element at an index of any type convertible to
+    /// `usize`. An error is returned, leaving the tree unchanged, if the
+    /// index can't be converted or is past the last element.
+    ///
+    pub fn set_at<I>(&mut self, idx: I, value: T) -> Result<(), FenwickError>
+    where
+        I: TryInto<usize>,
+    {
+        let idx = self.to_index(idx)?;
+        self.set(idx, value);
+        Ok(())
+    }
+
     /// Returns the sum of elements from `idx_i` to `idx_j` inclusive, Similar 
     /// to `.prefix_sum(idx_j) - .prefix_sum(idx_i - 1)`, but faster.
     ///
+    #[inline]
     pub fn range_sum(&self, idx_i: usize, idx_j: usize) -> T {
         debug_assert!(idx_i <= idx_j && idx_j <= self.end());
-        let (mut sum, mut i, mut j) = {
-            if idx_i > 0 { (T::default(), idx_i - 1, idx_j) } 
-            else         { (self.data[0],         0, idx_j) }
-        };
+        if idx_i == 0 {
+            return self.prefix_sum_raw(idx_j);
+        }
+        // Both walks only visit nodes at or below `idx_j`.
+        let data    = &self.data[..=idx_j];
+        let mut sum = T::default();
+        let mut i   = idx_i - 1;
+        let mut j   = idx_j;
+        
         while j > i {
-            sum += self.data[j];
+            sum += data[j];
             j   -= lsb!(j);
         }
         while i > j {
-            sum -= self.data[i];
+            sum -= data[i];
             i   -= lsb!(i);
         }
         sum
     }
+
+    /// Returns the inclusive range sum for each `(start, end)` pair in
+    /// `queries`, in the order given. The queries are answered in order of
+    /// their end indices so that consecutive queries walk nearby nodes, which
+    /// keeps more of the tree in cache than answering them as they come.
+    /// This is still `O(q log q + q log n)` time-complexity for `q` queries,
+    /// but is faster in practice for large batches.
+    ///
+    pub fn batch_range_sum_sorted(&self, queries: &[(usize, usize)]) 
+        -> Vec<T> 
+    {
+        let mut order = (0..queries.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&q| (queries[q].1, queries[q].0));
+        
+        let mut sums = vec![T::default(); queries.len()];
+        for q in order {
+            let (start, end) = queries[q];
+            sums[q] = self.range_sum(start, end);
+        }
+        sums
+    }
     
     /// Returns the sum of elements from `idx_i` to `idx_j` non-inclusive, 
     /// Similar to `.prefix_sum(idx_j - 1) - .prefix_sum(idx_i - 1)`, but
@@ -218,26 +881,182 @@ where
         }
     }
 
+    /// Returns the sum of elements over the half-open range `[start, end)`,
+    /// or the default value (zero) when `start == end`. Unlike `.range_sum2()`,
+    /// `end` may be one past the last element, so `.range_sum_exclusive(0, 
+    /// .end() + 1)` covers the whole tree.
+    ///
+    pub fn range_sum_exclusive(&self, start: usize, end: usize) -> T {
+        debug_assert!(start <= end && end <= self.size);
+        if start == end {
+            T::default()
+        } else {
+            self.range_sum(start, end - 1)
+        }
+    }
+
+    /// Returns the sum of elements over the half-open range `[start, end)`
+    /// as `.range_sum_exclusive()` does. By convention an empty range, where
+    /// `start == end`, sums to zero (`T::default()`), even at `.end() + 1`.
+    /// Rather than panicking, an error is returned if `start` is after `end`
+    /// or if `end` is more than one past the last element, reporting `end`
+    /// as the offending index. The bounds are checked in all builds, so this
+    /// suits untrusted input.
+    ///
+    pub fn checked_range_sum(&self, start: usize, end: usize) 
+        -> Result<T, FenwickError> 
+    {
+        if start > end {
+            Err(FenwickError::InvalidRange { start, end })
+        } else if end > self.size {
+            Err(FenwickError::IndexOutOfBounds { idx: end, end: self.end() })
+        } else {
+            Ok(self.range_sum_exclusive(start, end))
+        }
+    }
+
+    /// Returns the sum of every element except the one with index `idx`, as
+    /// for leave-one-out computations. This method has `O(log n)`
+    /// time-complexity.
+    ///
+    pub fn sum_excluding(&self, idx: usize) -> T {
+        debug_assert!(idx <= self.end());
+        self.total() - self.get(idx)
+    }
+
+    /// Returns the inclusive range sums on either side of `pivot` as the
+    /// pair `(.range_sum(start, pivot), .range_sum(pivot + 1, end))`, for
+    /// divide-and-conquer over the tree. The prefix sum at `pivot` is shared
+    /// by both sums, so this takes three `O(log n)` walks rather than four.
+    /// Panics unless `start <= pivot < end <= .end()`.
+    ///
+    pub fn split_range_sum(&self, start: usize, pivot: usize, end: usize)
+        -> (T, T)
+    {
+        assert!(start <= pivot && pivot < end && end <= self.end(),
+                "Indices must satisfy start <= pivot < end <= .end().");
+        let before = if start == 0 {
+            T::default()
+        } else {
+            self.prefix_sum_raw(start - 1)
+        };
+        let middle = self.prefix_sum_raw(pivot);
+        let after  = self.prefix_sum_raw(end);
+        (middle - before, after - middle)
+    }
+
     /// Find the largest index with `.prefix_sum(index) <= value`.
     /// NOTE: Requires all values are non-negative.
     ///
     pub fn rank_query(&self, value: T) -> usize {
         debug_assert!(self.data.iter().all(|&n| n >= T::default()),
                       "All elements must be non-negative to use this feature.");
-        let mut i = 0;
-        let mut j = self.size - 1;
-        let mut v = value - self.data[0];
+        self.rank_descent(value).0
+    }
+
+    /// Find the largest index with `.prefix_sum(index) <= value` as
+    /// `.rank_query()` does, and return it along with the amount of `value`
+    /// left over past that prefix sum, `value - .prefix_sum(index)`. This is
+    /// useful for positioning within a bucket. Returns `None` if even the
+    /// first element is greater than `value`.
+    /// NOTE: Requires all values are non-negative.
+    ///
+    pub fn rank_query_with_remainder(&self, value: T) -> Option<(usize, T)> {
+        debug_assert!(self.data.iter().all(|&n| n >= T::default()),
+                      "All elements must be non-negative to use this feature.");
+        if value < self.data[0] {
+            None
+        } else {
+            Some(self.rank_descent(value))
+        }
+    }
+
+    /// Find the largest index whose suffix sum, the sum of the elements from
+    /// the index through `.end()`, is `>= value`. This mirrors
+    /// `.rank_query()` from the other end of the tree: since the suffix sums
+    /// never increase, the answer is one past the largest index with
+    /// `.prefix_sum(index) <= .total() - value`. Returns `None` if `value`
+    /// is greater than the total. This method has `O(log n)`
+    /// time-complexity.
+    /// NOTE: Requires all values are non-negative.
+    ///
+    pub fn suffix_rank_query(&self, value: T) -> Option<usize> {
+        let total = self.total();
+        if value > total {
+            return None;
+        }
+        let rest = total - value;
+        if rest < self.data[0] {
+            Some(0)
+        } else {
+            Some((self.rank_query(rest) + 1).min(self.end()))
+        }
+    }
+
+    /// Find the largest prefix sum `<= value` and return the first or last
+    /// index having it, as selected by `tie`. `Tie::Last` gives the same
+    /// index as `.rank_query()`. Returns `None` if even the first element is
+    /// greater than `value`.
+    /// NOTE: Requires all values are non-negative.
+    ///
+    pub fn rank_query_with(&self, value: T, tie: Tie) -> Option<usize> {
+        if value < self.data[0] {
+            return None;
+        }
+        let last = self.rank_query(value);
+        match tie {
+            Tie::Last => Some(last),
+            Tie::First => {
+                let sum = self.prefix_sum(last);
+                if self.data[0] >= sum {
+                    Some(0)
+                } else {
+                    Some(self.last_below(sum) + 1)
+                }
+            },
+        }
+    }
+
+    /// Returns the largest index with `.prefix_sum(index) <= value` and the
+    /// smallest index with `.prefix_sum(index) >= value`, or `None` for
+    /// either where no such index exists. When `value` lies between two
+    /// prefix sums, the indices are adjacent; when it matches exactly, the
+    /// second is at or before the first. Both are found in a single descent
+    /// of the tree, in `O(log n)` time-complexity.
+    /// NOTE: Requires all values are non-negative.
+    ///
+    pub fn bracket(&self, value: T) -> (Option<usize>, Option<usize>) {
+        debug_assert!(self.data.iter().all(|&n| n >= T::default()),
+                      "All elements must be non-negative to use this feature.");
+        let first = self.data[0];
+        if value < first {
+            return (None, Some(0));
+        }
+        // Descend for the last prefix sum <= value and the last < value side
+        // by side; the one after the latter is the first >= value.
+        let mut i_le = 0;
+        let mut i_lt = 0;
+        let mut v_le = value - first;
+        let mut v_lt = v_le;
+        let mut j    = self.size - 1;
         
         while j > 0 {
-            if i + j < self.size && self.data[i + j] <= v {
-                v -= self.data[i + j];
-                i += j;
+            if i_le + j < self.size && self.data[i_le + j] <= v_le {
+                v_le -= self.data[i_le + j];
+                i_le += j;
+            }
+            if i_lt + j < self.size && self.data[i_lt + j] < v_lt {
+                v_lt -= self.data[i_lt + j];
+                i_lt += j;
             }
             j >>= 1;
         }
-        i
+        let upper = if value == first         { Some(0) } 
+                    else if i_lt < self.end() { Some(i_lt + 1) } 
+                    else                      { None };
+        (Some(i_le), upper)
     }
-    
+
     /// Find the smallest index with `.prefix_sum(index) >= value` - if there is
     /// an index where the prefix sum is >= value; however, if not the case,
     /// this method will return the index of the last element with a non-0 
@@ -252,7 +1071,11 @@ where
         } else {
             let mut i = self.end();
             let mut d = self.data[i];
-            let mut v = (value - self.data[0]).min(d);
+            let mut v = value - self.data[0];
+            
+            if d < v {
+                v = d;
+            }
             
             while i & 0x01 == 0 {
                 if d < v {
@@ -270,328 +1093,3015 @@ where
             }
         }
     }
-}
 
-impl<T> From<Vec<T>> for Fenwick<T>
-where
-    T: Add<Output = T> + Sub<Output = T> + AddAssign + SubAssign + Ord + 
-       Default + Copy, 
-{
-    fn from(vec: Vec<T>) -> Self {
-        Self::from_vec(vec)
+    /// Returns whether some index has a prefix sum exactly equal to `value`.
+    /// Since the prefix sums never decrease, only the smallest index with
+    /// `.prefix_sum(index) >= value` (from `.min_rank_query()`) needs to be
+    /// checked. This method has `O(log n)` time-complexity.
+    /// NOTE: Requires all values are non-negative.
+    ///
+    pub fn has_prefix_sum(&self, value: T) -> bool {
+        self.prefix_sum(self.min_rank_query(value)) == value
     }
-}
 
-impl<T> From<&[T]> for Fenwick<T>
-where
-    T: Add<Output = T> + Sub<Output = T> + AddAssign + SubAssign + Ord + 
-       Default + Copy, 
-{
-    fn from(slice: &[T]) -> Self {
-        Self::from_slice(slice)
+    /// Returns a cursor for answering `.min_rank_query()` style queries with
+    /// non-decreasing values by scanning forward, rather than descending the
+    /// tree for each one.
+    ///
+    pub fn rank_cursor(&self) -> RankCursor<'_, T> {
+        RankCursor::new(self)
     }
-}
 
-impl<T> FromIterator<T> for Fenwick<T>
-where
-    T: Add<Output = T> + Sub<Output = T> + AddAssign + SubAssign + Ord + 
-       Default + Copy, 
-{
-    fn from_iter<I>(iter: I) -> Self
+    /// Returns the prefix sum at every index as a vector. This is computed
+    /// from the element values with a running sum, in `O(n)` time-complexity.
+    ///
+    pub fn prefix_sums_vec(&self) -> Vec<T> {
+        self.accumulate()
+    }
+
+    /// Returns the sum of the prefix sums at every index, `Σ .prefix_sum(i)`
+    /// for `i` in `0..=.end()`, as an area-under-the-curve metric. This
+    /// equals `Σ (.end() + 1 - i) * .get(i)`, but is computed with a running
+    /// sum over the element values, in `O(n)` time-complexity without any
+    /// multiplication. Note that `.iter().sum()` is not the same thing: the
+    /// iterator yields element values, not prefix sums.
+    ///
+    pub fn sum_of_prefix_sums(&self) -> T {
+        let mut area = T::default();
+        for sum in self.prefix_sums() {
+            area += sum;
+        }
+        area
+    }
+
+    /// Writes the prefix sum at every index into `out`, without allocating.
+    /// This is useful in hot loops where a buffer can be reused. Panics if
+    /// `out` doesn't have exactly `.end() + 1` elements. This method has 
+    /// `O(n)` time-complexity.
+    ///
+    pub fn accumulate_into(&self, out: &mut [T]) {
+        assert_eq!(out.len(), self.size, 
+                   "The buffer must have one slot per element.");
+        for (slot, sum) in out.iter_mut().zip(self.prefix_sums()) {
+            *slot = sum;
+        }
+    }
+
+    /// Returns an iterator over the differences of consecutive prefix sums,
+    /// `.prefix_sum(i) - .prefix_sum(i - 1)` for each index `i` (taking the
+    /// prefix sum before index 0 as zero). These differences are the element
+    /// values. The differences are taken from a running sum as the prefix
+    /// sums are streamed, in amortized `O(1)` time each and without
+    /// allocating.
+    ///
+    pub fn prefix_diffs(&self) -> impl Iterator<Item = T> + '_ {
+        let mut prev = T::default();
+        self.prefix_sums().map(move |sum| {
+            let diff = sum - prev;
+            prev = sum;
+            diff
+        })
+    }
+
+    /// Walks the prefix sums in increasing index order and returns the first
+    /// index whose prefix sum satisfies `pred`, along with that prefix sum. 
+    /// Returns `None` if no prefix sum does. The walk stops as soon as `pred`
+    /// holds, taking amortized `O(1)` time per index visited.
+    ///
+    pub fn cumulative_until<F>(&self, pred: F) -> Option<(usize, T)>
     where
-        I: IntoIterator<Item = T>,
+        F: Fn(T) -> bool,
     {
-        Self::from_vec(iter.into_iter().collect::<Vec<T>>())
+        let mut sum = T::default();
+        for i in 0..self.size {
+            sum += self.node_value(i);
+            if pred(sum) {
+                return Some((i, sum));
+            }
+        }
+        None
     }
-}
 
-pub struct FenwickIntoIter<T> {
-    fw  : Fenwick<T>,
-    idx : usize,
-}
-
-impl<T> Iterator for FenwickIntoIter<T>
-where
-    T: Add<Output = T> + Sub<Output = T> + AddAssign + SubAssign + Ord + 
-       Default + Copy, 
-{
-    type Item = T;
-    
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.idx < self.fw.end() {
-            self.idx += 1;
-            Some(self.fw.get(self.idx - 1))
+    /// Binary searches the prefix sums with a comparator function, mirroring
+    /// `slice::binary_search_by()`. The comparator returns the ordering of
+    /// the prefix sum passed to it relative to the target. If a prefix sum
+    /// matches, `Ok` is returned with its index, otherwise `Err` is returned
+    /// with the index where the target could be inserted while keeping the
+    /// prefix sums ordered. The search descends the tree in `O(log n)`.
+    /// NOTE: Requires all values are non-negative, so the prefix sums are
+    /// monotonic.
+    ///
+    pub fn binary_search_by<F>(&self, f: F) -> Result<usize, usize>
+    where
+        F: Fn(T) -> Ordering,
+    {
+        debug_assert!(self.data.iter().all(|&n| n >= T::default()),
+                      "All elements must be non-negative to use this feature.");
+        let mut sum = self.data[0];
+        
+        match f(sum) {
+            Ordering::Less    => {},
+            Ordering::Equal   => return Ok(0),
+            Ordering::Greater => return Err(0),
+        }
+        // Find the last index with a prefix sum ordered before the target.
+        let mut i = 0;
+        let mut j = self.size - 1;
+        
+        while j > 0 {
+            if i + j < self.size && f(sum + self.data[i + j]) == Ordering::Less {
+                sum += self.data[i + j];
+                i   += j;
+            }
+            j >>= 1;
+        }
+        if i == self.end() {
+            Err(self.size)
+        } else if f(sum + self.get(i + 1)) == Ordering::Equal {
+            Ok(i + 1)
         } else {
-            None
+            Err(i + 1)
         }
     }
-}
 
-impl<T> IntoIterator for Fenwick<T>
-where
-    T: Add<Output = T> + Sub<Output = T> + AddAssign + SubAssign + Ord + 
-       Default + Copy, 
-{
-    type Item = T;
-    type IntoIter = FenwickIntoIter<T>;
-    
-    fn into_iter(self) -> Self::IntoIter {
-        FenwickIntoIter { fw: self, idx: 0 }
+    /// Adds `deltas[i]` to each element `i`. Panics unless there's exactly
+    /// one delta per element (`.end() + 1` of them). Since the tree nodes are
+    /// linear in the element values, the deltas are built into nodes of their
+    /// own and added node by node, giving `O(n)` time-complexity rather than
+    /// the `O(n log n)` of calling `.add()` for each index.
+    ///
+    pub fn apply_delta_vec(&mut self, deltas: &[T]) {
+        assert_eq!(deltas.len(), self.size,
+                   "There must be one delta per element.");
+        let mut nodes = deltas.to_vec();
+        Self::build_slice(&mut nodes);
+        
+        for (node, delta) in self.data.iter_mut().zip(nodes) {
+            *node += delta;
+        }
+        assert_invariants!(self);
     }
-}
-
-pub struct FenwickIter<'a, T> {
-    fw  : &'a Fenwick<T>,
-    idx : usize,
-}
 
-impl<'a, T> Iterator for FenwickIter<'a, T>
-where
-    T: Add<Output = T> + Sub<Output = T> + AddAssign + SubAssign + Ord + 
-       Default + Copy, 
-{
-    type Item = T;
-    
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.idx < self.fw.end() {
-            self.idx += 1;
-            Some(self.fw.get(self.idx - 1))
+    /// Assigns each `(idx, value)` pair in `assignments`. Where several pairs
+    /// target the same index, the last one wins. Few assignments are applied
+    /// one by one in `O(k log n)`; when there are enough of them that this
+    /// would cost more than a rebuild, the element values are recovered,
+    /// overwritten, and rebuilt into nodes in `O(n + k)` instead.
+    ///
+    pub fn bulk_set(&mut self, assignments: &[(usize, T)]) {
+        debug_assert!(assignments.iter().all(|&(idx, _)| idx <= self.end()));
+        let log_n = (usize::BITS - self.size.leading_zeros()) as usize;
+        
+        if assignments.len() * log_n < self.size {
+            for &(idx, value) in assignments {
+                self.set(idx, value);
+            }
         } else {
-            None
+            let mut values = self.decumulate();
+            for &(idx, value) in assignments {
+                values[idx] = value;
+            }
+            Self::build_slice(&mut values);
+            self.data = values;
         }
+        assert_invariants!(self);
     }
-}
 
-impl<'a, T> IntoIterator for &'a Fenwick<T>
-where
-    T: Add<Output = T> + Sub<Output = T> + AddAssign + SubAssign + Ord + 
-       Default + Copy, 
-{
-    type Item = T;
-    type IntoIter = FenwickIter<'a, T>;
+    /// Adds `delta` to every element, including any padding past the values
+    /// the tree was created from. Node `i` would gain `delta * lsb(i)`, but
+    /// rather than requiring a `Mul` bound and a conversion from `usize`,
+    /// the element values are recovered, shifted and rebuilt into nodes.
+    /// This has `O(n)` time-complexity and works for any element type.
+    ///
+    pub fn shift_values(&mut self, delta: T) {
+        let mut values = self.decumulate();
+        for value in values.iter_mut() {
+            *value += delta;
+        }
+        Self::build_slice(&mut values);
+        self.data = values;
+        assert_invariants!(self);
+    }
 
-    fn into_iter(self) -> Self::IntoIter {
-        FenwickIter { fw: self, idx: 0 }
+    /// Adds each `(idx, delta)` pair in `updates` to the tree. The updates
+    /// are first sorted by index and the deltas for the same index summed,
+    /// so each index is updated once and the nodes are visited in order,
+    /// which is kinder to the cache than applying a large batch at random.
+    /// NOTE: `updates` is left sorted and coalesced. This method has
+    /// `O(k log k + k log n)` time-complexity for `k` updates.
+    ///
+    pub fn update_many_sorted(&mut self, updates: &mut Vec<(usize, T)>) {
+        debug_assert!(updates.iter().all(|&(idx, _)| idx <= self.end()));
+        updates.sort_by_key(|&(idx, _)| idx);
+        updates.dedup_by(|next, kept| {
+            if next.0 == kept.0 {
+                kept.1 += next.1;
+                true
+            } else {
+                false
+            }
+        });
+        for &(idx, delta) in updates.iter() {
+            self.add_raw(idx, delta);
+        }
+        assert_invariants!(self);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::*;
-    
-    #[test]
-    fn new() {
-        let fw = Fenwick::<i32>::new(8);
-        assert_eq!(fw.end(), 8);
+    /// Sets every element to zero, keeping the tree's size. This method has
+    /// `O(n)` time-complexity.
+    ///
+    pub fn reset(&mut self) {
+        for node in self.data.iter_mut() {
+            *node = T::default();
+        }
+    }
+
+    /// Returns the total of all the elements and resets the tree, for
+    /// reporting a total per period while carrying on accumulating. Because
+    /// this takes `&mut self`, no update can come between the read and the
+    /// reset; note it is not atomic if the tree is shared across threads
+    /// behind a lock that's released in between. This method has `O(n)`
+    /// time-complexity.
+    ///
+    pub fn take_total(&mut self) -> T {
+        let total = self.total();
+        self.reset();
+        total
+    }
+
+    /// Updates the tree from the element values `old` to `new`, applying
+    /// only the differences: each index where the two differ is adjusted by
+    /// `new[i] - old[i]`. Both slices must hold one value per element, and
+    /// `old` should match the tree's current values. This has `O(n)`
+    /// time-complexity for the scan plus `O(log n)` per changed value.
+    ///
+    pub fn diff_update(&mut self, old: &[T], new: &[T]) {
+        assert!(old.len() == self.size && new.len() == self.size,
+                "There must be one old and one new value per element.");
+        for (idx, (&o, &n)) in old.iter().zip(new).enumerate() {
+            if n > o {
+                self.add_raw(idx, n - o);
+            } else if n < o {
+                self.sub_raw(idx, o - n);
+            }
+        }
+        assert_invariants!(self);
+    }
+
+    /// Moves `amount` of the value of element `idx` to element `move_to`, as
+    /// when splitting a histogram bucket. Element `idx` keeps the rest of its
+    /// value, `.get(idx) - amount`, and the total is unchanged. This method
+    /// has `O(log n)` time-complexity. Panics, in all builds, if either index
+    /// is past `.end()` or if `amount` exceeds the value of element `idx`.
+    ///
+    pub fn split_value_at(&mut self, idx: usize, move_to: usize, amount: T) {
+        assert!(idx <= self.end() && move_to <= self.end(),
+                "Both indices must be within the tree.");
+        assert!(amount <= self.get(idx),
+                "Can't move more than the element's value.");
+        self.sub(idx, amount);
+        self.add(move_to, amount);
+    }
+
+    /// Appends `values` after the last element (`.end()`), growing the tree.
+    /// The new size is adjusted to be 1 + a power of 2 as in `.new()`, with
+    /// any extra elements set to zero. Only the nodes covering the new slots
+    /// are computed, so this has `O(m + log n)` time-complexity where `m` is
+    /// the number of slots added - much cheaper than a series of pushes. In
+    /// `Mode::RangeUpdatePointQuery` mode the appended values are the elements
+    /// read by `.point_get()`, and the differences between them are stored.
+    ///
+    pub fn extend_from_slice(&mut self, values: &[T]) {
+        if values.is_empty() {
+            return;
+        }
+        let old_size = self.size;
+        let new_size = padded_size(old_size + values.len());
+        
+        self.data.reserve_exact(new_size - old_size);
+        match self.mode {
+            Mode::PointUpdateRangeQuery => {
+                self.data.extend_from_slice(values);
+                self.data.resize(new_size, T::default());
+            },
+            Mode::RangeUpdatePointQuery => {
+                let pads     = new_size - old_size - values.len();
+                let padding  = std::iter::repeat(T::default()).take(pads);
+                let mut prev = self.total();
+                for value in values.iter().copied().chain(padding) {
+                    self.data.push(value - prev);
+                    prev = value;
+                }
+            },
+        }
+        
+        // The existing nodes are complete; push those with parents in the
+        // new region up, then build the new region as usual.
+        let mut i = old_size - 1;
+        while i > 0 {
+            let j = i + lsb!(i);
+            if j >= old_size && j < new_size {
+                let d = self.data[i];
+                self.data[j] += d;
+            }
+            i -= lsb!(i);
+        }
+        for i in old_size..new_size {
+            let j = i + lsb!(i);
+            if j < new_size {
+                let d = self.data[i];
+                self.data[j] += d;
+            }
+        }
+        self.size = new_size;
+        assert_invariants!(self);
+    }
+
+    /// Adds the value of each element of `other` to the element of this tree
+    /// at the same position shifted by `offset`, so element `i` of `other`
+    /// is added to element `offset + i`. This folds a smaller tree, such as
+    /// one built per region, into a larger one. Panics unless all of
+    /// `other`'s elements, including any padding, fit: `offset + other.end()
+    /// <= .end()`, and panics if the two trees' modes differ. This method has
+    /// `O(m log n)` time-complexity, where `m` is the number of elements in
+    /// `other`.
+    ///
+    pub fn add_tree_at(&mut self, other: &Self, offset: usize) {
+        assert_eq!(self.mode, other.mode,
+                   "The trees must be in the same mode.");
+        assert!(offset + other.end() <= self.end(),
+                "The other tree doesn't fit at the offset.");
+        for (i, value) in other.values().enumerate() {
+            self.add_raw(offset + i, value);
+        }
+        // In range-update mode `other` holds differences; cancel their sum
+        // after its last element so it doesn't carry on to the rest.
+        let next = offset + other.size;
+        if self.mode == Mode::RangeUpdatePointQuery && next < self.size {
+            self.sub_raw(next, other.total());
+        }
+        assert_invariants!(self);
+    }
+
+    /// Returns a new tree laying the elements of `other` end to end after
+    /// those of this tree. Element `i` of `other` becomes element
+    /// `.end() + 1 + i` of the result, so both trees keep their padding. As
+    /// in `.new()`, the size is adjusted to be 1 + a power of 2, with any
+    /// extra elements set to zero. The values of both trees are recovered and
+    /// rebuilt into the new tree in `O(n)` time-complexity. The new tree is
+    /// used in the same mode; panics if the two trees' modes differ.
+    ///
+    pub fn concat(&self, other: &Self) -> Self {
+        assert_eq!(self.mode, other.mode,
+                   "The trees must be in the same mode.");
+        let mut values = self.element_values();
+        values.extend(other.element_values());
+        
+        let size = padded_size(values.len());
+        values.resize(size, T::default());
+        Self::from_element_values(values, self.mode)
+    }
+
+    /// Resizes the tree to hold `new_size` elements, like `Vec::resize()`.
+    /// When growing, the new elements are set to `fill`; when shrinking, the
+    /// elements from `new_size` on are discarded. As in `.new()`, the size is
+    /// then adjusted to be 1 + a power of 2, with any extra elements set to
+    /// zero. The tree is rebuilt in `O(n)` time-complexity and keeps its
+    /// mode; in `Mode::RangeUpdatePointQuery` mode the elements are those
+    /// read by `.point_get()`.
+    ///
+    pub fn resize(&mut self, new_size: usize, fill: T) {
+        let mut values = self.element_values();
+        values.resize(new_size, fill);
+        values.resize(padded_size(new_size), T::default());
+        
+        *self = Self::from_element_values(values, self.mode);
+        assert_invariants!(self);
+    }
+
+    /// Inserts `count` zero-valued elements before index 0, shifting every
+    /// existing element up by `count` so `.get(count + i)` returns what
+    /// `.get(i)` did before. The size is adjusted to be 1 + a power of 2 as in
+    /// `.new()`. The tree is rebuilt in `O(n)` time-complexity and keeps its
+    /// mode, as with `.resize()`.
+    ///
+    pub fn pad_front(&mut self, count: usize) {
+        if count == 0 {
+            return;
+        }
+        let size       = padded_size(self.size + count);
+        let mut values = Vec::with_capacity(size);
+        values.resize(count, T::default());
+        values.extend(self.element_values());
+        values.resize(size, T::default());
+        
+        *self = Self::from_element_values(values, self.mode);
+        assert_invariants!(self);
+    }
+
+    /// Folds every element value (not prefix sum) into an accumulator,
+    /// beginning with `init`. The elements are visited in index order. The
+    /// values are streamed from `.values()` in `O(n)` time-complexity
+    /// overall, without allocating.
+    ///
+    pub fn fold<B, F>(&self, init: B, f: F) -> B
+    where
+        F: FnMut(B, T) -> B,
+    {
+        self.values().fold(init, f)
+    }
+
+    /// Returns the element values (the difference array of the prefix sums)
+    /// as a vector. This is the minimal representation of the tree's logical
+    /// state; building a tree from it with `.from()` reproduces this tree.
+    /// This method has `O(n)` time-complexity.
+    ///
+    pub fn to_difference_array(&self) -> Vec<T> {
+        self.decumulate()
+    }
+
+    /// Returns the per-element value differences between this tree and
+    /// `other`, `self.get(i) - other.get(i)` for each index `i`. Panics if
+    /// the trees differ in size. This method has `O(n)` time-complexity.
+    ///
+    pub fn difference(&self, other: &Fenwick<T>) -> Vec<T> {
+        assert_eq!(self.size, other.size, "The trees must be the same size.");
+        self.decumulate().into_iter()
+            .zip(other.decumulate())
+            .map(|(a, b)| a - b)
+            .collect()
+    }
+
+    /// Returns the indices whose values have changed since `snapshot` was
+    /// taken (e.g. with `.clone()`), each paired with `new_value - old_value`.
+    /// Panics if the trees differ in size. This method has `O(n)`
+    /// time-complexity.
+    ///
+    pub fn delta_since(&self, snapshot: &Fenwick<T>) -> Vec<(usize, T)> {
+        assert_eq!(self.size, snapshot.size, 
+                   "The trees must be the same size.");
+        self.values()
+            .zip(snapshot.values())
+            .enumerate()
+            .filter(|(_, (new, old))| new != old)
+            .map(|(i, (new, old))| (i, new - old))
+            .collect()
+    }
+
+    /// Returns a new tree whose element values combine the corresponding
+    /// values of this tree and `other` using `f`, e.g. element-wise maximum.
+    /// The new tree is used in the same mode. Panics if the trees differ in
+    /// size or mode. This method has `O(n)` time-complexity.
+    ///
+    pub fn zip_with<F>(&self, other: &Fenwick<T>, f: F) -> Fenwick<T>
+    where
+        F: Fn(T, T) -> T,
+    {
+        assert_eq!(self.size, other.size, "The trees must be the same size.");
+        assert_eq!(self.mode, other.mode,
+                   "The trees must be in the same mode.");
+        let values = self.element_values().into_iter()
+                         .zip(other.element_values())
+                         .map(|(a, b)| f(a, b))
+                         .collect();
+        Self::from_element_values(values, self.mode)
+    }
+
+    /// Returns a coarser tree with `buckets` elements, each holding the sum
+    /// of a contiguous range of this tree's elements. Bucket `b` covers the
+    /// indices from `b * n / buckets` up to, but not including, 
+    /// `(b + 1) * n / buckets` for `n = .end() + 1`, so when `n` isn't a 
+    /// multiple of `buckets` the bucket sizes differ by at most one, with the
+    /// larger buckets spread evenly. The new tree's size is adjusted as in
+    /// `.new()`, and it's used in the same mode. This method has 
+    /// `O(b log n)` time-complexity, or `O(n)` in 
+    /// `Mode::RangeUpdatePointQuery` mode, where the elements read by
+    /// `.point_get()` are recovered and summed.
+    ///
+    pub fn histogram(&self, buckets: usize) -> Fenwick<T> {
+        debug_assert!(buckets > 0, "There must be at least one bucket.");
+        let n        = self.size;
+        let bounds   = |b: usize| b * n / buckets;
+        let mut sums = match self.mode {
+            Mode::PointUpdateRangeQuery => (0..buckets).map(|b| {
+                self.range_sum_exclusive(bounds(b), bounds(b + 1))
+            }).collect::<Vec<_>>(),
+            Mode::RangeUpdatePointQuery => {
+                let values = self.element_values();
+                (0..buckets).map(|b| {
+                    values[bounds(b)..bounds(b + 1)].iter()
+                        .fold(T::default(), |sum, &v| sum + v)
+                }).collect()
+            },
+        };
+        sums.resize(padded_size(buckets), T::default());
+        Self::from_element_values(sums, self.mode)
+    }
+
+    /// Returns the approximate number of bytes the tree occupies: the node
+    /// array's allocated capacity plus the tree struct itself.
+    ///
+    pub fn estimate_memory(&self) -> usize {
+        self.data.capacity() * size_of::<T>() + size_of::<Self>()
+    }
+
+    /// Checks the tree's internal invariants, returning `false` if any are
+    /// violated: the node array must hold exactly `.end() + 1` nodes, its size
+    /// must be 1 + a power of 2, and decumulating the nodes into element
+    /// values and rebuilding must reproduce them exactly. This is meant for
+    /// debugging and fuzzing. Note that rounding can make the rebuild of a
+    /// float tree differ slightly. This method has `O(n)` time-complexity.
+    ///
+    pub fn validate(&self) -> bool {
+        if self.data.len() != self.size || !is_tree_size(self.size) {
+            return false;
+        }
+        let mut nodes = self.decumulate();
+        Self::build_slice(&mut nodes);
+        nodes == self.data
+    }
+
+    /// Returns an immutable snapshot of the tree. Taking the snapshot copies
+    /// the tree's data, in `O(n)` time-complexity; clones of the view then
+    /// share that copy rather than making their own. Later updates to this
+    /// tree don't affect the view.
+    ///
+    pub fn to_view(&self) -> FenwickView<T> {
+        FenwickView::new(self.clone())
+    }
+
+    /// Consumes the tree and returns a read-only version of it that doesn't
+    /// offer any of the mutating methods.
+    ///
+    pub fn freeze(self) -> FrozenFenwick<T> {
+        FrozenFenwick::new(self)
+    }
+
+    /// Recovers the value of element `i` by subtracting the nodes that node
+    /// `i` accumulated when the tree was built. This has amortized `O(1)`
+    /// time-complexity when visiting every node.
+    ///
+    fn node_value(&self, i: usize) -> T {
+        let mut value = self.data[i];
+        if i > 0 {
+            let mut step = 1;
+            while step < lsb!(i) {
+                value -= self.data[i - step];
+                step <<= 1;
+            }
+        }
+        value
+    }
+
+    /// Returns the largest index with `.prefix_sum(index) <= value` and the
+    /// part of `value` remaining after that prefix sum.
+    ///
+    fn rank_descent(&self, value: T) -> (usize, T) {
+        let mut i = 0;
+        let mut j = self.size - 1;
+        let mut v = value - self.data[0];
+        
+        while j > 0 {
+            if i + j < self.size && self.data[i + j] <= v {
+                v -= self.data[i + j];
+                i += j;
+            }
+            j >>= 1;
+        }
+        (i, v)
+    }
+
+    /// Returns the largest index with `.prefix_sum(index) < value`. The caller
+    /// ensures the first element is below `value`.
+    ///
+    fn last_below(&self, value: T) -> usize {
+        let mut i = 0;
+        let mut j = self.size - 1;
+        let mut v = value - self.data[0];
+
+        while j > 0 {
+            if i + j < self.size && self.data[i + j] < v {
+                v -= self.data[i + j];
+                i += j;
+            }
+            j >>= 1;
+        }
+        i
+    }
+
+    /// Returns an error if `idx` is past the last element.
+    ///
+    fn check_index(&self, idx: usize) -> Result<(), FenwickError> {
+        if idx < self.size {
+            Ok(())
+        } else {
+            Err(FenwickError::IndexOutOfBounds { idx, end: self.end() })
+        }
+    }
+
+    /// Converts `idx` to a `usize` and checks it's not past the last element.
+    ///
+    fn to_index<I>(&self, idx: I) -> Result<usize, FenwickError>
+    where
+        I: TryInto<usize>,
+    {
+        let idx = idx.try_into().map_err(|_| FenwickError::InvalidIndex)?;
+        self.check_index(idx)?;
+        Ok(idx)
+    }
+
+    /// Converts the element values in `data` into tree nodes in place.
+    ///
+    fn build_slice(data: &mut [T]) {
+        let size = data.len();
+        for i in 1..size {
+            let j = i + lsb!(i);
+            if j < size {
+                data[j] = build_add(data[j], data[i], j);
+            }
+        }
+    }
+
+    /// Returns the value of every element: the values themselves, or in
+    /// `Mode::RangeUpdatePointQuery` mode the prefix sums of the difference
+    /// array the tree holds, as `.point_get()` reads them. This method has
+    /// `O(n)` time-complexity.
+    ///
+    fn element_values(&self) -> Vec<T> {
+        match self.mode {
+            Mode::PointUpdateRangeQuery => self.decumulate(),
+            Mode::RangeUpdatePointQuery => self.accumulate(),
+        }
+    }
+
+    /// Returns the prefix sum at every index, computed from the element
+    /// values with a running sum in `O(n)` time-complexity.
+    ///
+    fn accumulate(&self) -> Vec<T> {
+        let mut sums = self.decumulate();
+        for i in 1..sums.len() {
+            let prev = sums[i - 1];
+            sums[i] += prev;
+        }
+        sums
+    }
+
+    /// Converts the tree nodes in `data` back into element values in place.
+    ///
+    fn decumulate_slice(data: &mut [T]) {
+        let size = data.len();
+        for i in (1..size).rev() {
+            let j = i + lsb!(i);
+            if j < size {
+                let d = data[i];
+                data[j] -= d;
+            }
+        }
+    }
+
+}
+
+impl<T> Fenwick<T>
+where 
+    T: Add<Output = T> + Sub<Output = T> + AddAssign + SubAssign + 
+       PartialOrd + Default + Copy + Into<f64>, 
+{
+    /// Returns `true` if both trees have the same size and each pair of
+    /// corresponding element values differ by no more than `epsilon`. This is
+    /// intended for trees of floats, where rounding makes exact comparison
+    /// unreliable. This method has `O(n)` time-complexity.
+    ///
+    pub fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.size == other.size && 
+        self.decumulate().into_iter()
+            .zip(other.decumulate())
+            .all(|(a, b)| (a.into() - b.into()).abs() <= epsilon)
+    }
+    
+    /// Returns the mean of the element values as a float. All `.end() + 1`
+    /// elements are included, whether or not they were ever set, so the
+    /// padding after the values a tree was built from counts as zeros; use
+    /// `.mean_of()` to leave it out. This method has `O(1)` time-complexity.
+    ///
+    pub fn mean(&self) -> f64 {
+        self.total().into() / self.size as f64
+    }
+
+    /// Returns the mean of the first `len` element values as a float, such as
+    /// the buckets of a histogram without the padding after them. Panics if
+    /// `len` is zero or more than `.end() + 1`. This method has `O(log n)`
+    /// time-complexity.
+    ///
+    pub fn mean_of(&self, len: usize) -> f64 {
+        assert!(len > 0 && len <= self.size,
+                "The length must be from 1 to the number of elements.");
+        self.prefix_sum(len - 1).into() / len as f64
+    }
+
+    /// Returns the sum of the elements from index 0 to `idx` inclusive as a
+    /// float, for ratio computations without casts at the call site. Each
+    /// node is converted before it's summed, so the result is correct even
+    /// where the sum would overflow `T`. This method has `O(log n)`
+    /// time-complexity.
+    ///
+    pub fn prefix_sum_f64(&self, idx: usize) -> f64 {
+        debug_assert!(idx <= self.end());
+        let mut sum = self.data[0].into();
+        let mut i   = idx;
+        while i != 0 {
+            sum += self.data[i].into();
+            i   -= lsb!(i);
+        }
+        sum
+    }
+
+    /// Returns the total of all the elements as a float, converting the
+    /// nodes as `.prefix_sum_f64()` does. This method has `O(1)`
+    /// time-complexity.
+    ///
+    pub fn total_f64(&self) -> f64 {
+        self.data[0].into() + self.data[self.end()].into()
+    }
+    
+    /// Returns the variance of the element values as a float. This is the 
+    /// population variance (dividing by `n`, not `n - 1`) since the tree holds
+    /// the complete set of values rather than a sample of them. As with
+    /// `.mean()`, all `.end() + 1` elements are included; use
+    /// `.variance_of()` to leave out the padding. This method has `O(n)`
+    /// time-complexity.
+    ///
+    pub fn variance(&self) -> f64 {
+        self.variance_of(self.size)
+    }
+
+    /// Returns the population variance of the first `len` element values as
+    /// a float, as `.variance()` does for all of them. Panics if `len` is
+    /// zero or more than `.end() + 1`. This method has `O(len)`
+    /// time-complexity.
+    ///
+    pub fn variance_of(&self, len: usize) -> f64 {
+        let mean = self.mean_of(len);
+        self.values().take(len).fold(0.0, |acc, v| {
+            let dev = v.into() - mean;
+            acc + dev * dev
+        }) / len as f64
+    }
+}
+
+impl<T> Fenwick<T>
+where 
+    T: Add<Output = T> + Sub<Output = T> + AddAssign + SubAssign + 
+       PartialOrd + Default + Copy + Mul<Output = T>, 
+{
+    /// Returns the sum of the squares of the element values. This method has
+    /// `O(n)` time-complexity.
+    ///
+    pub fn sum_of_squares(&self) -> T {
+        self.fold(T::default(), |acc, v| acc + v * v)
+    }
+}
+
+impl<T> Fenwick<T>
+where 
+    T: Add<Output = T> + Sub<Output = T> + AddAssign + SubAssign + Ord + 
+       Default + Copy, 
+{
+    /// Clamps the value of element `idx` into the range `[min, max]`. This is
+    /// useful for bounded counters after a series of adjustments that may
+    /// have over- or undershot. Panics if `min > max`.
+    ///
+    pub fn clamp_element(&mut self, idx: usize, min: T, max: T) {
+        let value = self.get(idx);
+        self.saturating_set(idx, value, min, max);
+    }
+    
+    /// Sets element `idx` to `value` clamped into the range `[min, max]`.
+    /// Panics if `min > max`.
+    ///
+    pub fn saturating_set(&mut self, idx: usize, value: T, min: T, max: T) {
+        debug_assert!(idx <= self.end());
+        let cur_val = self.get(idx);
+        let new_val = value.clamp(min, max);
+        if new_val != cur_val {
+            self.set(idx, new_val);
+        }
+    }
+
+    /// Compares the totals of this tree and `other`. This is convenient for
+    /// sorting collections of trees, e.g. `trees.sort_by(Fenwick::cmp_total)`.
+    ///
+    pub fn cmp_total(&self, other: &Self) -> Ordering {
+        self.total().cmp(&other.total())
+    }
+
+    /// Returns the index at which the prefix sum is greatest, or `None` if
+    /// the tree is empty. When several indices share the maximum, the first
+    /// of them is returned. The prefix sums are streamed from
+    /// `.prefix_sums()` in a single `O(n)` pass without allocating.
+    ///
+    pub fn max_prefix_index(&self) -> Option<usize> {
+        let mut best = None;
+        let mut max  = T::default();
+        
+        for (i, sum) in self.prefix_sums().enumerate() {
+            if best.is_none() || sum > max {
+                best = Some(i);
+                max  = sum;
+            }
+        }
+        best
+    }
+
+    /// Returns the index of the first element with a nonzero value, or `None`
+    /// if every element is zero. The values are scanned in `O(n)` time.
+    ///
+    pub fn first_nonzero(&self) -> Option<usize> {
+        self.values().position(|v| v != T::default())
+    }
+
+    /// Returns the index of the last element with a nonzero value, or `None`
+    /// if every element is zero. The values are scanned from the end in
+    /// `O(n)` time.
+    ///
+    pub fn last_nonzero(&self) -> Option<usize> {
+        (0..self.size).rev().find(|&i| self.node_value(i) != T::default())
+    }
+}
+
+/// The magic number and format version starting the encoding produced by
+/// `.to_bytes()`.
+///
+const BYTES_HEADER: &[u8; 4] = b"FWK2";
+
+impl Fenwick<u64> {
+    /// Creates a frequency tree by tallying the occurrences of each key in
+    /// `keys`, so `.get(k)` is the number of times `k` appeared. Keys outside
+    /// `0..size` are skipped. The size is adjusted as in `.new()`. This
+    /// function has `O(n + k)` time-complexity for `k` keys.
+    ///
+    pub fn from_counts<I>(size: usize, keys: I) -> Self
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        let mut counts = Vec::with_capacity(padded_size(size));
+        counts.resize(size, 0);
+        
+        for key in keys {
+            if key < size {
+                counts[key] += 1;
+            }
+        }
+        Self::from_vec(counts)
+    }
+
+    /// Merges the sorted slices `a` and `b` and returns a frequency tree over
+    /// the distinct values along with those values, in increasing order.
+    /// Index `i` of the tree holds the number of times `keys[i]` appears in
+    /// `a` and `b` together, so the tree can answer order-statistics queries
+    /// such as those used in merge-based inversion counting. Both slices must
+    /// be sorted. This function has `O(n + m)` time-complexity.
+    ///
+    pub fn from_sorted_merge<U>(a: &[U], b: &[U]) -> (Self, Vec<U>)
+    where
+        U: Ord + Copy,
+    {
+        debug_assert!(a.windows(2).all(|w| w[0] <= w[1]) &&
+                      b.windows(2).all(|w| w[0] <= w[1]),
+                      "Both slices must be sorted.");
+        let mut keys   = Vec::with_capacity(a.len() + b.len());
+        let mut counts = Vec::<u64>::with_capacity(a.len() + b.len());
+        let (mut i, mut j) = (0, 0);
+        
+        while i < a.len() || j < b.len() {
+            let key = if j == b.len() || (i < a.len() && a[i] <= b[j]) {
+                i += 1;
+                a[i - 1]
+            } else {
+                j += 1;
+                b[j - 1]
+            };
+            match keys.last() {
+                Some(&last) if last == key => *counts.last_mut().unwrap() += 1,
+                _ => {
+                    keys.push(key);
+                    counts.push(1);
+                },
+            }
+        }
+        (Self::from_vec(counts), keys)
+    }
+    
+    /// Returns how many items have a value (key) in `[lo, hi]`, for trees 
+    /// whose indices are value buckets holding counts, such as those built
+    /// by `.from_counts()`. This is `.range_sum(lo, hi)` with the keys
+    /// bounds-checked rather than asserted: keys past the last bucket have
+    /// no items, so `hi` is clamped to `.end()`, and an empty range (`lo > hi`
+    /// or `lo` past the last bucket) counts zero items.
+    ///
+    pub fn count_in_range(&self, lo: u64, hi: u64) -> u64 {
+        let end = self.end();
+        let hi  = usize::try_from(hi).map_or(end, |hi| hi.min(end));
+        match usize::try_from(lo) {
+            Ok(lo) if lo <= hi => self.range_sum(lo, hi),
+            _                  => 0,
+        }
+    }
+
+    /// Draws an index at random with probability proportional to its value,
+    /// treating the element values as weights. `rng` must return uniformly
+    /// distributed `u64`s; it is called once per draw. Returns `None` if the
+    /// total weight is zero. The random value is scaled into `[0, total)`
+    /// by a widening multiply, which avoids the skew of taking a remainder.
+    /// This method has `O(log n)` time-complexity.
+    ///
+    pub fn weighted_sample<R>(&self, rng: &mut R) -> Option<usize>
+    where
+        R: FnMut() -> u64,
+    {
+        let total = self.total();
+        if total == 0 {
+            return None;
+        }
+        let r = ((rng() as u128 * total as u128) >> 64) as u64;
+        Some(self.min_rank_query(r + 1))
+    }
+
+    /// Encodes the tree in a compact binary format: the 4 byte header
+    /// `b"FWK2"` (a magic number and format version), a mode byte (0 for
+    /// `Mode::PointUpdateRangeQuery`, 1 for `Mode::RangeUpdatePointQuery`),
+    /// then the number of elements and each element's value, as
+    /// little-endian `u64`s. Element values rather than tree nodes are
+    /// stored, so the format doesn't depend on the tree's internal layout;
+    /// in `Mode::RangeUpdatePointQuery` mode these are the differences that
+    /// `.point_get()` sums. This method has `O(n)` time-complexity.
+    ///
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mode = match self.mode {
+            Mode::PointUpdateRangeQuery => 0,
+            Mode::RangeUpdatePointQuery => 1,
+        };
+        let mut bytes = Vec::with_capacity(13 + self.size * 8);
+        bytes.extend_from_slice(BYTES_HEADER);
+        bytes.push(mode);
+        bytes.extend_from_slice(&(self.size as u64).to_le_bytes());
+        for value in self.values() {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Decodes a tree encoded by `.to_bytes()`, rebuilding its nodes from the
+    /// element values in the encoded mode. An error is returned if the header
+    /// or mode byte doesn't match or the length doesn't agree with the
+    /// element count. This function has `O(n)` time-complexity.
+    ///
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, FenwickError> {
+        if bytes.len() < 13 || &bytes[..4] != BYTES_HEADER {
+            return Err(FenwickError::InvalidEncoding);
+        }
+        let mode = match bytes[4] {
+            0 => Mode::PointUpdateRangeQuery,
+            1 => Mode::RangeUpdatePointQuery,
+            _ => return Err(FenwickError::InvalidEncoding),
+        };
+        let mut count = [0; 8];
+        count.copy_from_slice(&bytes[5..13]);
+        let count = u64::from_le_bytes(count);
+        let body  = &bytes[13..];
+        
+        if count.checked_mul(8) != Some(body.len() as u64) {
+            return Err(FenwickError::InvalidEncoding);
+        }
+        let values = body.chunks_exact(8).map(|chunk| {
+            let mut value = [0; 8];
+            value.copy_from_slice(chunk);
+            u64::from_le_bytes(value)
+        });
+        let mut fw = Self::from_vec_unpadded(values.collect());
+        fw.mode    = mode;
+        Ok(fw)
+    }
+
+    /// Draws `k` independent indices as in `.weighted_sample()`, with
+    /// replacement, so the same index may be drawn more than once. Returns an
+    /// empty vector if the total weight is zero. The total is computed once
+    /// for all the draws, giving `O(log n + k log n)` time-complexity.
+    ///
+    pub fn weighted_sample_n<R>(&self, k: usize, rng: &mut R) -> Vec<usize>
+    where
+        R: FnMut() -> u64,
+    {
+        let total = self.total();
+        if total == 0 {
+            return Vec::new();
+        }
+        (0..k).map(|_| {
+            let r = ((rng() as u128 * total as u128) >> 64) as u64;
+            self.min_rank_query(r + 1)
+        }).collect()
+    }
+}
+
+/// Trees are equal when they're used in the same `Mode` and hold the same
+/// element values. Sizes needn't match: the shorter tree is treated as
+/// continuing with zeros, so a tree padded with trailing zeros equals the
+/// unpadded tree of the same values. Comparison takes `O(n)` time-complexity.
+///
+impl<T> PartialEq for Fenwick<T>
+where
+    T: Add<Output = T> + Sub<Output = T> + AddAssign + SubAssign + 
+       PartialOrd + Default + Copy, 
+{
+    fn eq(&self, other: &Self) -> bool {
+        let len  = self.size.max(other.size);
+        let pads = std::iter::repeat(T::default());
+        let lhs  = self.values().chain(pads.clone()).take(len);
+        let rhs  = other.values().chain(pads).take(len);
+        
+        self.mode == other.mode && lhs.eq(rhs)
+    }
+}
+
+impl<T> From<Vec<T>> for Fenwick<T>
+where
+    T: Add<Output = T> + Sub<Output = T> + AddAssign + SubAssign + 
+       PartialOrd + Default + Copy, 
+{
+    fn from(vec: Vec<T>) -> Self {
+        Self::from_vec(vec)
+    }
+}
+
+impl<T> From<&[T]> for Fenwick<T>
+where
+    T: Add<Output = T> + Sub<Output = T> + AddAssign + SubAssign + 
+       PartialOrd + Default + Copy, 
+{
+    fn from(slice: &[T]) -> Self {
+        Self::from_slice(slice)
+    }
+}
+
+impl<T> FromIterator<T> for Fenwick<T>
+where
+    T: Add<Output = T> + Sub<Output = T> + AddAssign + SubAssign + 
+       PartialOrd + Default + Copy, 
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let iter = iter.into_iter();
+        
+        // When the exact length is known, allocate the padded tree up front so
+        // the values are built in place without reallocating.
+        match iter.size_hint() {
+            (lower, Some(upper)) if lower == upper => {
+                let mut data = Vec::with_capacity(padded_size(lower));
+                data.extend(iter);
+                Self::from_vec(data)
+            },
+            _ => Self::from_vec(iter.collect::<Vec<T>>()),
+        }
+    }
+}
+
+pub struct FenwickIntoIter<T> {
+    fw  : Fenwick<T>,
+    idx : usize,
+}
+
+impl<T> Iterator for FenwickIntoIter<T>
+where
+    T: Add<Output = T> + Sub<Output = T> + AddAssign + SubAssign + 
+       PartialOrd + Default + Copy, 
+{
+    type Item = T;
+    
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.idx < self.fw.end() {
+            self.idx += 1;
+            Some(self.fw.get(self.idx - 1))
+        } else {
+            None
+        }
+    }
+
+    /// The hint is exact: the number of elements before `.end()` not yet
+    /// yielded.
+    ///
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.fw.end().saturating_sub(self.idx);
+        (len, Some(len))
+    }
+
+    /// Returns the number of remaining elements in `O(1)` time-complexity.
+    ///
+    fn count(self) -> usize {
+        self.fw.end().saturating_sub(self.idx)
+    }
+
+    /// Skips directly to the `n`th remaining element, taking `O(log n)`
+    /// time-complexity rather than calling `.next()` `n + 1` times.
+    ///
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.idx = self.idx.saturating_add(n).min(self.fw.end());
+        self.next()
+    }
+
+    /// Returns the value of the element before `.end()`, the last one the
+    /// iterator yields, in `O(log n)` time-complexity.
+    ///
+    fn last(self) -> Option<Self::Item> {
+        let end = self.fw.end();
+        if self.idx < end {
+            Some(self.fw.get(end - 1))
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> IntoIterator for Fenwick<T>
+where
+    T: Add<Output = T> + Sub<Output = T> + AddAssign + SubAssign + 
+       PartialOrd + Default + Copy, 
+{
+    type Item = T;
+    type IntoIter = FenwickIntoIter<T>;
+    
+    fn into_iter(self) -> Self::IntoIter {
+        FenwickIntoIter { fw: self, idx: 0 }
+    }
+}
+
+pub struct FenwickIter<'a, T> {
+    fw  : &'a Fenwick<T>,
+    idx : usize,
+}
+
+impl<'a, T> Iterator for FenwickIter<'a, T>
+where
+    T: Add<Output = T> + Sub<Output = T> + AddAssign + SubAssign + 
+       PartialOrd + Default + Copy, 
+{
+    type Item = T;
+    
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.idx < self.fw.end() {
+            self.idx += 1;
+            Some(self.fw.get(self.idx - 1))
+        } else {
+            None
+        }
+    }
+
+    /// The hint is exact: the number of elements before `.end()` not yet
+    /// yielded.
+    ///
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.fw.end().saturating_sub(self.idx);
+        (len, Some(len))
+    }
+
+    /// Returns the number of remaining elements in `O(1)` time-complexity.
+    ///
+    fn count(self) -> usize {
+        self.fw.end().saturating_sub(self.idx)
+    }
+
+    /// Skips directly to the `n`th remaining element, taking `O(log n)`
+    /// time-complexity rather than calling `.next()` `n + 1` times.
+    ///
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.idx = self.idx.saturating_add(n).min(self.fw.end());
+        self.next()
+    }
+
+    /// Returns the value of the element before `.end()`, the last one the
+    /// iterator yields, in `O(log n)` time-complexity.
+    ///
+    fn last(self) -> Option<Self::Item> {
+        let end = self.fw.end();
+        if self.idx < end {
+            Some(self.fw.get(end - 1))
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Fenwick<T>
+where
+    T: Add<Output = T> + Sub<Output = T> + AddAssign + SubAssign + 
+       PartialOrd + Default + Copy, 
+{
+    type Item = T;
+    type IntoIter = FenwickIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        FenwickIter { fw: self, idx: 0 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    
+    #[test]
+    fn new() {
+        let fw = Fenwick::<i32>::new(8);
+        assert_eq!(fw.end(), 8);
+    }
+    
+    #[test]
+    fn add() {
+        let mut fw = Fenwick::new(8);
+        fw.add(1, 1);
+        fw.add(2, 1);
+        fw.add(1, 1);
+        assert_eq!(fw.prefix_sum(2), 3); // Range is inclusive.
+        
+        assert_eq!(fw.prefix_sum(fw.end()), 3);
+        fw.add(fw.end(), 3);
+        assert_eq!(fw.prefix_sum(fw.end()), 6);
+        assert_eq!(fw.prefix_sum(fw.end() - 1), 3);
+    }
+    
+    #[test]
+    fn sub() {
+        let mut fw = Fenwick::new(8);
+        fw.add(1, 1);
+        fw.add(2, 1);
+        fw.add(2, 1);
+        fw.sub(2, 1);
+        assert_eq!(fw.prefix_sum(2), 2);
+    }
+    
+    #[test] 
+    fn prefix_sum() {
+        let mut fw = Fenwick::new(8);
+        fw.add(1, 1);  // sum = 2
+        fw.add(0, 1);  // sum = 1
+        fw.add(3, 1);  // sum = 3
+        assert_eq!(fw.prefix_sum(3), 3);
+        
+        fw.sub(0, 1);
+        assert_eq!(fw.prefix_sum(3), 2);
+    }
+    
+    #[test]
+    fn get() {
+        let mut fw = Fenwick::new(8);
+        fw.add(0, 5);
+        fw.add(1, 4);
+        assert_eq!(fw.get(1), 4);
+        assert_eq!(fw.get(0), 5);
+        assert_eq!(fw.get(fw.end()), 0);
+    }
+    
+    #[test]
+    fn set() {
+        let mut fw = Fenwick::new(8);
+        fw.add(0, 5);
+        fw.add(1, 4);
+        assert_eq!(fw.get(1), 4);
+        assert_eq!(fw.get(0), 5);
+        
+        fw.set(1, 0);
+        assert_eq!(fw.get(1), 0);
+        assert_eq!(fw.prefix_sum(8), 5);
+        
+        assert_eq!(fw.get(0), 5);
+        fw.set(0, 4);
+        assert_eq!(fw.get(0), 4);
+        
+        fw.set(fw.end(), 3);
+        assert_eq!(fw.get(fw.end()), 3);
+        assert_eq!(fw.prefix_sum(fw.end()), 7);
+        assert_eq!(fw.total(), 7);
+        
+        fw.set(0, 8);
+        assert_eq!(fw.get(0), 8);
+        fw.set(0, 0);
+        assert_eq!(fw.get(0), 0);
+    }
+    
+    #[test]
+    fn range_sum() {
+        let mut fw = Fenwick::new(8);
+        fw.add(0, 1);  // sum = 1
+        fw.add(1, 1);  // sum = 2 
+        fw.add(2, 3);  // sum = 5
+        fw.add(3, 1);  // sum = 6
+        fw.add(4, 1);  // sum = 7
+        
+        assert_eq!(fw.range_sum(1, 3), 5);
+        assert_eq!(fw.range_sum(0, 3), 6);
+        
+        assert_eq!(fw.range_sum(2, 4), 5);
+        assert_eq!(fw.range_sum(0, 0), 1);
+        assert_eq!(fw.range_sum(2, 2), 3);
+    }
+    
+    #[test] 
+    fn range_sum2() {
+        let mut fw = Fenwick::new(8);
+        fw.add(0, 1);  // sum = 1
+        fw.add(1, 1);  // sum = 2 
+        fw.add(2, 3);  // sum = 5
+        fw.add(3, 1);  // sum = 6
+        fw.add(4, 1);  // sum = 7
+        
+        assert_eq!(fw.range_sum2(1, 3), 4);
+        assert_eq!(fw.range_sum2(0, 3), 5);
+        
+        assert_eq!(fw.range_sum2(2, 4), 4);
+        assert_eq!(fw.range_sum2(0, 0), 0);
+        assert_eq!(fw.range_sum2(2, 2), 0);
+    }
+
+    #[test]
+    fn rank_query() {
+        let mut fw = Fenwick::new(8);
+        fw.add(0, 1);  // sum = 1
+        fw.add(1, 1);  // sum = 2 
+        fw.add(2, 3);  // sum = 5
+        fw.add(3, 1);  // sum = 6
+        fw.add(4, 1);  // sum = 7
+
+        assert_eq!(fw.rank_query(0), 0);
+        assert_eq!(fw.rank_query(1), 0);
+        assert_eq!(fw.rank_query(2), 1);
+        assert_eq!(fw.rank_query(7), 8);
+        
+        assert_eq!(fw.rank_query(5), 2);
+        assert_eq!(fw.rank_query(6), 3);
+        
+        fw.set(0, 0);
+        assert_eq!(fw.rank_query(5), 3);
+    }
+    
+    #[test]
+    fn min_rank_query() {
+        let mut fw = Fenwick::new(8);
+        fw.add(0, 1);  // sum = 1
+        fw.add(1, 1);  // sum = 2 
+        fw.add(2, 3);  // sum = 5
+        fw.add(3, 1);  // sum = 6
+        fw.add(4, 1);  // sum = 7
+
+        assert_eq!(fw.min_rank_query(1), 0);
+        
+        assert_eq!(fw.min_rank_query(3), 2);  // Check basic.
+        assert_eq!(fw.min_rank_query(8), 4);  // Check that it falls on min.
+        
+        fw.add(7, 3);  // sum = 10
+        
+        assert_eq!(fw.min_rank_query(7), 4);  // Should fall to 4.
+        assert_eq!(fw.min_rank_query(8), 7);  // Should advance to 7.
+
+        assert_eq!(fw.min_rank_query(10), 7);
+        assert_eq!(fw.min_rank_query(11), 7);
+    }
+
+    #[test]
+    fn iterators() {
+        let mut fw = Fenwick::new(8);
+        fw.add(0, 1);  // sum = 1
+        fw.add(1, 1);  // sum = 2 
+        fw.add(2, 3);  // sum = 5
+        fw.add(3, 1);  // sum = 6
+        fw.add(4, 1);  // sum = 7
+        
+        let mut iter = fw.iter();
+        assert_eq!(iter.next(), Some(1));
+        assert_eq!(iter.next(), Some(1));
+        assert_eq!(iter.next(), Some(3));
+        assert_eq!(iter.next(), Some(1));
+        assert_eq!(iter.next(), Some(1));
+        assert_eq!(iter.next(), Some(0));
+        assert_eq!(iter.next(), Some(0));
+        assert_eq!(iter.next(), Some(0));
+        assert_eq!(iter.next(), None);
+        
+        let mut iter = fw.into_iter();
+        assert_eq!(iter.next(), Some(1));
+        assert_eq!(iter.next(), Some(1));
+        assert_eq!(iter.next(), Some(3));
+        assert_eq!(iter.next(), Some(1));
+        assert_eq!(iter.next(), Some(1));
+        assert_eq!(iter.next(), Some(0));
+        assert_eq!(iter.next(), Some(0));
+        assert_eq!(iter.next(), Some(0));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn fold() {
+        let mut fw = Fenwick::new(4);
+        for i in 0..=fw.end() {
+            fw.set(i, i as i64 + 1);
+        }
+        assert_eq!(fw.fold(1, |acc, n| acc * n), 120);
+        assert_eq!(fw.fold(0, |acc, n| acc + n), fw.total());
+        
+        fw.set(2, 0);
+        assert_eq!(fw.fold(1, |acc, n| acc * n), 0);
+        assert_eq!(fw.fold(0, |cnt, n| cnt + (n > 0) as usize), 4);
+    }
+
+    #[test]
+    fn from_iterator() {
+        let fw = Fenwick::from_iter(vec![1, 1, 3, 1, 1, 0, 0, 0]);
+        assert_eq!(fw.prefix_sum(3), 6);
+        assert_eq!(fw.prefix_sum(4), 7);
+        assert_eq!(fw.prefix_sum(5), 7);
+        assert_eq!(fw.prefix_sum(6), 7);
+        assert_eq!(fw.prefix_sum(7), 7);
+
+        let fw = vec![1, 1, 3, 1, 1, 0, 0, 0, 0]
+                    .into_iter().collect::<Fenwick<i32>>();
+        assert_eq!(fw.prefix_sum(3), 6);
+        assert_eq!(fw.prefix_sum(4), 7);
+        assert_eq!(fw.prefix_sum(5), 7);
+        assert_eq!(fw.prefix_sum(6), 7);
+        assert_eq!(fw.prefix_sum(7), 7);
+
+        let fw: Fenwick<_> = vec![1, 1, 3, 1, 1, 0, 0, 0, 0].into();
+        assert_eq!(fw.prefix_sum(3), 6);
+        assert_eq!(fw.prefix_sum(4), 7);
+        assert_eq!(fw.prefix_sum(5), 7);
+        assert_eq!(fw.prefix_sum(6), 7);
+        assert_eq!(fw.prefix_sum(7), 7);
+
+    }
+
+    #[test]
+    fn from_exact_size_iterator() {
+        let fw = vec![1, 1, 3, 1, 1].into_iter().collect::<Fenwick<i32>>();
+        assert_eq!(fw.end(), 8);
+        assert_eq!(fw.data.capacity(), fw.end() + 1);
+        assert_eq!(fw.prefix_sum(4), 7);
+        
+        let fw = vec![1; 8].into_iter().collect::<Fenwick<i32>>();
+        assert_eq!(fw.end(), 8);
+        assert_eq!(fw.data.capacity(), fw.end() + 1);
+        assert_eq!(fw.total(), 8);
+        
+        // Filtering loses the exact size hint, but still builds correctly.
+        let fw = (0..10).filter(|n| n % 2 == 0).collect::<Fenwick<i32>>();
+        assert_eq!(fw.end(), 8);
+        assert_eq!(fw.total(), 20);
+    }
+
+    #[test]
+    fn to_difference_array() {
+        let mut fw = Fenwick::from_vec(vec![1, 1, 3, 1, 1, 0, 2, 0, 4]);
+        assert_eq!(fw.to_difference_array()[..9], [1, 1, 3, 1, 1, 0, 2, 0, 4]);
+        
+        fw.add(3, 5);
+        fw.sub(8, 4);
+        let values = fw.to_difference_array();
+        assert_eq!(values[..9], [1, 1, 3, 6, 1, 0, 2, 0, 0]);
+        
+        let copy = Fenwick::from_vec(values);
+        assert_eq!(copy, fw);
+        assert_eq!(copy.data[..=fw.end()], fw.data[..]);
+    }
+
+    #[test]
+    fn range_sum_exclusive() {
+        let fw = Fenwick::from_vec(vec![1, 1, 3, 1, 1, 0, 0, 0, 2]);
+        
+        assert_eq!(fw.range_sum(1, 3), 5);
+        assert_eq!(fw.range_sum_exclusive(1, 3), 4);
+        
+        assert_eq!(fw.range_sum(2, 2), 3);
+        assert_eq!(fw.range_sum_exclusive(2, 2), 0);
+        assert_eq!(fw.range_sum_exclusive(2, 3), 3);
+        
+        assert_eq!(fw.range_sum(0, 8), 9);
+        assert_eq!(fw.range_sum_exclusive(0, 8), 7);
+        assert_eq!(fw.range_sum_exclusive(0, fw.end() + 1), 9);
+        assert_eq!(fw.range_sum_exclusive(8, 9), 2);
+    }
+
+    #[test]
+    fn approx_eq() {
+        let mut fw1 = Fenwick::<f64>::from_vec(vec![0.1, 0.2, 0.3, 0.4, 0.5]);
+        let mut fw2 = Fenwick::from_vec(vec![0.1, 0.2, 0.3, 0.4, 0.5]);
+        assert!((fw1.prefix_sum(2) - 0.6).abs() < 1e-15);
+        
+        fw1.add(3, 1e-12);
+        assert!(fw1.approx_eq(&fw2, 1e-9));
+        assert!(fw2.approx_eq(&fw1, 1e-9));
+        assert!(!fw1.approx_eq(&fw2, 1e-15));
+        
+        fw2.set(4, 0.6);
+        assert!(!fw1.approx_eq(&fw2, 1e-9));
+        
+        let fw3 = Fenwick::from_vec(vec![0.1, 0.2, 0.3]);
+        assert!(!fw1.approx_eq(&fw3, 1.0));
+    }
+
+    #[test]
+    fn clamp_element() {
+        let mut fw = Fenwick::from_vec(vec![1, 1, 3, 1, 1]);
+        fw.add(2, 20);
+        fw.sub(3, 5);
+        
+        fw.clamp_element(2, 0, 10);
+        assert_eq!(fw.get(2), 10);
+        fw.clamp_element(3, 0, 10);
+        assert_eq!(fw.get(3), 0);
+        fw.clamp_element(1, 0, 10);
+        assert_eq!(fw.get(1), 1);
+        assert_eq!(fw.total(), 13);
+        
+        fw.saturating_set(0, 100, 0, 5);
+        assert_eq!(fw.get(0), 5);
+        assert_eq!(fw.total(), 17);
+    }
+
+    #[test]
+    fn statistics() {
+        // A 9 bucket histogram: mean 5, variance 257 / 9 - 25 = 32 / 9.
+        let fw = Fenwick::from_vec(vec![2, 4, 4, 4, 5, 5, 7, 9, 5]);
+        assert_eq!(fw.sum_of_squares(), 257);
+        assert!((fw.mean_of(9) - 5.0).abs() < 1e-12);
+        assert!((fw.variance_of(9) - 32.0 / 9.0).abs() < 1e-12);
+        
+        let fw = Fenwick::from_vec(vec![3, 3, 3, 3]);
+        assert_eq!(fw.mean_of(4), 3.0);
+        assert_eq!(fw.variance_of(4), 0.0);
+        
+        // The whole-tree statistics also take in the zeroed padding.
+        let len = fw.end() + 1;
+        assert_eq!(fw.mean(), fw.mean_of(len));
+        assert_eq!(fw.variance(), fw.variance_of(len));
+        assert!(fw.variance() > 0.0);
+    }
+
+    #[test]
+    fn into_values() {
+        let values = vec![1, 1, 3, 1, 1, 0, 2, 0, 4];
+        let fw     = Fenwick::from_vec(values.clone());
+        assert_eq!(fw.into_values().collect::<Vec<_>>()[..9], values[..]);
+        
+        let mut fw = Fenwick::from_vec(vec![5, 4, 3, 2]);
+        fw.add(4, 1);
+        assert_eq!(fw.into_values().collect::<Vec<_>>(), vec![5, 4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn binary_search_by() {
+        let fw     = Fenwick::from_vec(vec![1, 1, 3, 1, 1, 0, 0, 0, 2]);
+        let prefix = [1, 2, 5, 6, 7, 7, 7, 7, 9, 9, 9, 9, 9, 9, 9, 9, 9];
+        
+        assert_eq!(fw.binary_search_by(|s| s.cmp(&1)), Ok(0));
+        assert_eq!(fw.binary_search_by(|s| s.cmp(&5)), Ok(2));
+        assert_eq!(fw.binary_search_by(|s| s.cmp(&9)), Ok(8));
+        assert_eq!(fw.binary_search_by(|s| s.cmp(&7)).map(|i| prefix[i]), 
+                   Ok(7));
+        
+        assert_eq!(fw.binary_search_by(|s| s.cmp(&0)), Err(0));
+        assert_eq!(fw.binary_search_by(|s| s.cmp(&4)), Err(2));
+        assert_eq!(fw.binary_search_by(|s| s.cmp(&8)), Err(8));
+        assert_eq!(fw.binary_search_by(|s| s.cmp(&10)), Err(17));
+        
+        for v in 0..11 {
+            let expected = prefix.binary_search_by(|s| s.cmp(&v));
+            match fw.binary_search_by(|s| s.cmp(&v)) {
+                Ok(i)  => assert_eq!(prefix[i], v),
+                Err(i) => assert_eq!(Err(i), expected),
+            }
+            assert_eq!(expected.is_ok(), 
+                       fw.binary_search_by(|s| s.cmp(&v)).is_ok());
+        }
+    }
+
+    #[test]
+    fn extend_from_slice() {
+        let mut values = vec![1, 1, 3, 1, 1, 0, 2, 0, 4];
+        let mut fw     = Fenwick::from_vec(values.clone());
+        
+        for block in &[vec![5], vec![2, 7, 1], vec![], vec![3; 20]] {
+            values.resize(fw.end() + 1, 0);
+            values.extend_from_slice(block);
+            fw.extend_from_slice(block);
+            
+            // Extending by nothing leaves the tree as it is, while rebuilding
+            // pads the values again.
+            let rebuilt = Fenwick::from_vec(values.clone());
+            assert_eq!(fw, rebuilt);
+            if !block.is_empty() {
+                assert_eq!(fw.data, rebuilt.data);
+            }
+        }
+        assert_eq!(fw.end(), 128);
+        assert_eq!(fw.total(), 88);
+    }
+
+    #[test]
+    fn pad_front() {
+        let old    = Fenwick::from_vec(vec![1, 1, 3, 1, 1, 0, 2, 0, 4]);
+        let mut fw = old.clone();
+        
+        fw.pad_front(3);
+        assert_eq!(fw.end(), 32);
+        assert_eq!(fw.prefix_sum(2), 0);
+        for i in 0..=old.end() {
+            assert_eq!(fw.get(3 + i), old.get(i));
+            assert_eq!(fw.prefix_sum(3 + i), old.prefix_sum(i));
+        }
+        assert_eq!(fw.total(), old.total());
+        
+        fw.pad_front(0);
+        assert_eq!(fw.end(), 32);
+        assert_eq!(fw.get(11), 4);
+    }
+
+    #[test]
+    fn from_counts() {
+        let fw = Fenwick::from_counts(4, vec![0, 0, 1, 3, 3, 3]);
+        assert_eq!(fw.get(0), 2);
+        assert_eq!(fw.get(1), 1);
+        assert_eq!(fw.get(2), 0);
+        assert_eq!(fw.get(3), 3);
+        assert_eq!(fw.total(), 6);
+        
+        // Out-of-range keys are skipped.
+        let fw = Fenwick::from_counts(4, vec![1, 4, 9, 1]);
+        assert_eq!(fw.get(1), 2);
+        assert_eq!(fw.get(4), 0);
+        assert_eq!(fw.total(), 2);
+    }
+
+    #[test]
+    fn difference() {
+        let old    = Fenwick::from_vec(vec![1, 1, 3, 1, 1]);
+        let mut fw = old.clone();
+        fw.add(1, 4);
+        fw.sub(3, 2);
+        fw.set(4, 9);
+        
+        let manual = (0..=fw.end()).map(|i| fw.get(i) - old.get(i))
+                                   .collect::<Vec<_>>();
+        assert_eq!(fw.difference(&old), manual);
+        assert_eq!(fw.difference(&old), vec![0, 4, 0, -2, 8, 0, 0, 0, 0]);
+        assert_eq!(old.difference(&fw), vec![0, -4, 0, 2, -8, 0, 0, 0, 0]);
+        assert_eq!(fw.difference(&fw), vec![0; 9]);
+    }
+
+    #[test]
+    fn apply_delta_vec() {
+        let mut fw1 = Fenwick::from_vec(vec![1, 1, 3, 1, 1, 0, 2, 0, 4]);
+        let mut fw2 = fw1.clone();
+        let mut deltas = vec![3, -1, 0, 0, 7, 2, -2, 5, 1];
+        deltas.resize(fw1.end() + 1, 0);
+        
+        fw1.apply_delta_vec(&deltas);
+        for (i, &delta) in deltas.iter().enumerate() {
+            fw2.add(i, delta);
+        }
+        assert_eq!(fw1.data, fw2.data);
+        assert_eq!(fw1.get(4), 8);
+        assert_eq!(fw1.total(), 28);
+    }
+
+    #[test]
+    fn max_prefix_index() {
+        // Single peak after index 3.
+        let fw = Fenwick::from_vec(vec![2, 1, -1, 4, -3, -1, 2, -5, 1]);
+        assert_eq!(fw.max_prefix_index(), Some(3));
+        
+        // Prefix sums 1, 1, 3, 3, 2: the first index reaching 3 wins.
+        let fw = Fenwick::from_vec(vec![1, 0, 2, 0, -1]);
+        assert_eq!(fw.max_prefix_index(), Some(2));
+        
+        let fw = Fenwick::<i32>::new(4);
+        assert_eq!(fw.max_prefix_index(), Some(0));
+        
+        let fw = Fenwick::from_vec(vec![-1, -2, 0, 0, 0]);
+        assert_eq!(fw.max_prefix_index(), Some(0));
+    }
+
+    #[test]
+    fn repeat() {
+        let fw = Fenwick::repeat(2, 8);
+        assert_eq!(fw.prefix_sum(7), 16);
+        assert_eq!(fw.get(8), 0);
+        assert_eq!(fw.total(), 16);
+        
+        for size in 0..40 {
+            let fw = Fenwick::repeat(3_u64, size);
+            assert_eq!(fw.data, Fenwick::from_vec(vec![3_u64; size]).data);
+        }
+    }
+
+    #[test]
+    fn wide_values() {
+        let big = i64::MAX as i128;
+        
+        let mut fw = Fenwick::<i128>::new(8);
+        fw.add(0, big);
+        fw.add(3, big);
+        fw.add(8, big);
+        fw.sub(5, 1);
+        assert_eq!(fw.prefix_sum(2), big);
+        assert_eq!(fw.prefix_sum(4), 2 * big);
+        assert_eq!(fw.prefix_sum(7), 2 * big - 1);
+        assert_eq!(fw.total(), 3 * big - 1);
+        assert_eq!(fw.range_sum(3, 8), 2 * big - 1);
+        
+        fw.set(3, -big);
+        assert_eq!(fw.get(3), -big);
+        assert_eq!(fw.total(), big - 1);
+        
+        let big = u64::MAX as u128;
+        
+        let mut fw = Fenwick::<u128>::from_vec(vec![big, big, 1, big, 0]);
+        assert_eq!(fw.prefix_sum(1), 2 * big);
+        assert_eq!(fw.total(), 3 * big + 1);
+        
+        fw.set(1, 5);
+        assert_eq!(fw.get(1), 5);
+        assert_eq!(fw.prefix_sum(2), big + 6);
+        assert_eq!(fw.rank_query(big + 6), 2);
+        assert_eq!(fw.min_rank_query(big + 7), 3);
+        assert_eq!(fw.to_difference_array()[..5], [big, 5, 1, big, 0]);
+    }
+
+    #[test]
+    fn count_in_range() {
+        let fw = Fenwick::from_counts(8, vec![0, 0, 1, 3, 3, 3, 5, 7, 7]);
+        assert_eq!(fw.count_in_range(0, 0), 2);
+        assert_eq!(fw.count_in_range(0, 3), 6);
+        assert_eq!(fw.count_in_range(2, 5), 4);
+        assert_eq!(fw.count_in_range(4, 4), 0);
+        assert_eq!(fw.count_in_range(6, 100), 2);
+        assert_eq!(fw.count_in_range(0, u64::MAX), 9);
+        assert_eq!(fw.count_in_range(5, 2), 0);
+        assert_eq!(fw.count_in_range(9, 12), 0);
+    }
+
+    #[test]
+    fn resize() {
+        let mut fw = Fenwick::from_vec(vec![1, 1, 3, 1]);
+        
+        fw.resize(7, 2);
+        assert_eq!(fw.end(), 8);
+        assert_eq!(fw.to_difference_array(), vec![1, 1, 3, 1, 0, 2, 2, 0, 0]);
+        assert_eq!(fw.prefix_sum(6), 10);
+        assert_eq!(fw.total(), 10);
+        
+        fw.resize(3, 0);
+        assert_eq!(fw.end(), 4);
+        assert_eq!(fw.to_difference_array(), vec![1, 1, 3, 0, 0]);
+        assert_eq!(fw.prefix_sum(1), 2);
+        assert_eq!(fw.total(), 5);
+        
+        // Shrinking within the same padded size still discards the tail.
+        let mut fw = Fenwick::from_vec(vec![1, 1, 3, 1, 1, 0, 2, 0, 4]);
+        fw.resize(6, 0);
+        assert_eq!(fw.end(), 8);
+        assert_eq!(fw.total(), 7);
+    }
+
+    #[test]
+    fn validate() {
+        let mut fw = Fenwick::from_vec(vec![1, 1, 3, 1, 1, 0, 2, 0, 4]);
+        assert!(fw.validate());
+        fw.add(3, 7);
+        fw.resize(12, 1);
+        assert!(fw.validate());
+        assert!(Fenwick::<u8>::new(0).validate());
+        
+        let mut bad = fw.clone();
+        bad.data.pop();
+        assert!(!bad.validate());
+        
+        let mut bad = fw.clone();
+        bad.size = 12;
+        bad.data.truncate(12);
+        assert!(!bad.validate());
+    }
+
+    #[test]
+    fn prefix_sum_checked() {
+        let fw = Fenwick::from_vec(vec![1, 1, 3, 1, 1]);
+        assert_eq!(fw.prefix_sum_checked(0), Some(1));
+        assert_eq!(fw.prefix_sum_checked(3), Some(6));
+        assert_eq!(fw.prefix_sum_checked(fw.end()), Some(7));
+        assert_eq!(fw.prefix_sum_checked(fw.end() + 1), None);
+        assert_eq!(fw.prefix_sum_checked(usize::MAX), None);
+    }
+
+    #[test]
+    fn get_set_checked() {
+        let mut fw = Fenwick::from_vec(vec![1, 1, 3, 1]);
+        assert_eq!(fw.get_checked(2), Some(3));
+        assert_eq!(fw.get_checked(fw.end()), Some(0));
+        assert_eq!(fw.get_checked(fw.end() + 1), None);
+        
+        assert_eq!(fw.set_checked(fw.end(), 4), Ok(()));
+        assert_eq!(fw.get(fw.end()), 4);
+        assert_eq!(fw.set_checked(5, 4), 
+                   Err(FenwickError::IndexOutOfBounds { idx: 5, end: 4 }));
+        assert_eq!(fw.total(), 10);
+    }
+
+    #[test]
+    fn set_checked_boundaries() {
+        let mut fw = Fenwick::from_vec(vec![1.5, 2.0, 0.5, 4.0]);
+        let before = fw.clone();
+        let end    = fw.end();
+        for idx in [end + 1, end + 2, 8, usize::MAX] {
+            assert_eq!(fw.set_checked(idx, 9.0),
+                       Err(FenwickError::IndexOutOfBounds { idx, end }));
+        }
+        assert_eq!(fw, before);
+        
+        assert_eq!(fw.set_checked(0, 3.0), Ok(()));
+        assert_eq!(fw.set_checked(end, 0.0), Ok(()));
+        assert_eq!(fw.decumulate(), [3.0, 2.0, 0.5, 4.0, 0.0]);
+        
+        let mut fw = Fenwick::<u8>::empty();
+        assert_eq!(fw.set_checked(fw.end(), 7), Ok(()));
+        assert_eq!(fw.set_checked(fw.end() + 1, 7), 
+                   Err(FenwickError::IndexOutOfBounds { idx: 2, end: 1 }));
+        assert_eq!(fw.total(), 7);
+    }
+
+    #[test]
+    fn prefix_diffs() {
+        let mut fw = Fenwick::from_vec(vec![1, 1, 3, 1, 1, 0, 2, 0, 4]);
+        fw.sub(6, 5);
+        
+        let diffs = fw.prefix_diffs().collect::<Vec<_>>();
+        assert_eq!(diffs, fw.to_difference_array());
+        assert_eq!(diffs[..9], [1, 1, 3, 1, 1, 0, -3, 0, 4]);
+        
+        let naive = (0..=fw.end()).map(|i| {
+            if i == 0 { fw.prefix_sum(0) }
+            else      { fw.prefix_sum(i) - fw.prefix_sum(i - 1) }
+        });
+        assert!(fw.prefix_diffs().eq(naive));
+    }
+
+    #[test]
+    fn cmp_total() {
+        let mut trees = [Fenwick::from_vec(vec![4, 4, 1]),
+                         Fenwick::from_vec(vec![1, 1, 3, 1, 1]),
+                         Fenwick::from_vec(vec![-2, 3]),
+                         Fenwick::repeat(3, 4)].to_vec();
+        assert_eq!(trees[0].cmp_total(&trees[1]), Ordering::Greater);
+        assert_eq!(trees[1].cmp_total(&trees[0]), Ordering::Less);
+        assert_eq!(trees[0].cmp_total(&trees[0]), Ordering::Equal);
+        
+        trees.sort_by(Fenwick::cmp_total);
+        let totals = trees.iter().map(|fw| fw.total()).collect::<Vec<_>>();
+        assert_eq!(totals, vec![1, 7, 9, 12]);
+    }
+
+    #[test]
+    fn prefix_sums_vec() {
+        let mut fw = Fenwick::from_vec(vec![1, 1, 3, 1, 1]);
+        assert_eq!(fw.prefix_sums_vec()[..5], [1, 2, 5, 6, 7]);
+        
+        fw.add(2, 4);
+        let naive = (0..=fw.end()).map(|i| fw.prefix_sum(i)).collect::<Vec<_>>();
+        assert_eq!(fw.prefix_sums_vec(), naive);
+    }
+
+    #[test]
+    fn cumulative_until() {
+        let fw = Fenwick::from_vec(vec![1, 1, 3, 1, 1, 0, 2, 0, 4]);
+        assert_eq!(fw.cumulative_until(|s| s > 4), Some((2, 5)));
+        assert_eq!(fw.cumulative_until(|s| s > 7), Some((6, 9)));
+        assert_eq!(fw.cumulative_until(|s| s >= 1), Some((0, 1)));
+        assert_eq!(fw.cumulative_until(|s| s > 13), None);
+        
+        let fw = Fenwick::from_vec(vec![3, -5, 4, -2, 1]);
+        assert_eq!(fw.cumulative_until(|s| s < 0), Some((1, -2)));
+    }
+
+    #[test]
+    fn named_iterators() {
+        let values = vec![1, 1, 3, 1, 1, 0, 2, 0, 4];
+        let sums   = vec![1, 2, 5, 6, 7, 7, 9, 9, 13];
+        let mut fw = Fenwick::from_vec(values[..8].to_vec());
+        fw.add(8, values[8]);
+        
+        assert_eq!(fw.values().collect::<Vec<_>>(), values);
+        assert_eq!(fw.to_difference_array(), values);
+        assert_eq!(fw.prefix_sums().collect::<Vec<_>>(), sums);
+        assert_eq!(fw.prefix_sums_vec(), sums);
+        
+        // `.iter()` yields values too, but stops before the last element.
+        assert_eq!(fw.iter().collect::<Vec<_>>(), values[..8].to_vec());
+        assert_eq!(fw.into_values().collect::<Vec<_>>(), values);
+    }
+
+    #[test]
+    fn delta_since() {
+        let mut fw   = Fenwick::from_vec(vec![1, 1, 3, 1, 1, 0, 2, 0, 4]);
+        let snapshot = fw.clone();
+        assert_eq!(fw.delta_since(&snapshot), vec![]);
+        
+        fw.add(2, 5);
+        fw.set(7, 3);
+        fw.add(4, 2);
+        fw.sub(4, 2);
+        assert_eq!(fw.delta_since(&snapshot), vec![(2, 5), (7, 3)]);
+        assert_eq!(snapshot.delta_since(&fw), vec![(2, -5), (7, -3)]);
+    }
+
+    #[test]
+    fn zip_with() {
+        let fw1 = Fenwick::from_vec(vec![1, 5, 3, 0, 2]);
+        let fw2 = Fenwick::from_vec(vec![4, 1, 3, 2, -1]);
+        
+        let max = fw1.zip_with(&fw2, |a, b| a.max(b));
+        assert_eq!(max.end(), fw1.end());
+        assert_eq!(max.to_difference_array()[..5], [4, 5, 3, 2, 2]);
+        assert_eq!(max.prefix_sum(2), 12);
+        assert_eq!(max.total(), 16);
+        
+        let sum = fw1.zip_with(&fw2, |a, b| a + b);
+        assert_eq!(sum.to_difference_array()[..5], [5, 6, 6, 2, 1]);
+    }
+
+    #[test]
+    fn histogram() {
+        let mut fw = Fenwick::from_vec(vec![1, 1, 3, 1, 1, 0, 2, 0]);
+        fw.add(8, 4);
+        
+        // Nine elements into four buckets: [0, 2), [2, 4), [4, 6), [6, 9).
+        let hist = fw.histogram(4);
+        assert_eq!(hist.to_difference_array(), vec![2, 4, 1, 6, 0]);
+        assert_eq!(hist.total(), fw.total());
+        
+        let hist = fw.histogram(3);
+        assert_eq!(hist.to_difference_array(), vec![5, 2, 6, 0, 0]);
+        
+        let hist = fw.histogram(1);
+        assert_eq!(hist.get(0), 13);
+        
+        let fw   = Fenwick::from_vec(vec![1, 2, 3, 4, 5, 6, 7, 8]);
+        let hist = fw.histogram(4);
+        assert_eq!(hist.to_difference_array(), vec![3, 7, 11, 15, 0]);
+    }
+
+    #[test]
+    fn accumulate_into() {
+        let mut fw  = Fenwick::from_vec(vec![1, 1, 3, 1]);
+        let mut buf = vec![0; fw.end() + 1];
+        
+        fw.accumulate_into(&mut buf);
+        assert_eq!(buf, fw.prefix_sums().collect::<Vec<_>>());
+        assert_eq!(buf, vec![1, 2, 5, 6, 6]);
+        
+        fw.add(1, 3);
+        fw.accumulate_into(&mut buf);
+        assert_eq!(buf, vec![1, 5, 8, 9, 9]);
     }
     
     #[test]
-    fn add() {
-        let mut fw = Fenwick::new(8);
-        fw.add(1, 1);
-        fw.add(2, 1);
-        fw.add(1, 1);
-        assert_eq!(fw.prefix_sum(2), 3); // Range is inclusive.
+    #[should_panic]
+    fn accumulate_into_wrong_length() {
+        let fw      = Fenwick::from_vec(vec![1, 1, 3, 1, 1]);
+        let mut buf = vec![0; 4];
+        fw.accumulate_into(&mut buf);
+    }
+
+    #[test]
+    fn rank_query_with_ties() {
+        let mut fw = Fenwick::from_vec(vec![0_u32, 2, 0, 0, 3, 0, 0, 0]);
+        fw.add(8, 1);
+        assert_eq!(fw.rank_query_with(0, Tie::First), Some(0));
+        assert_eq!(fw.rank_query_with(0, Tie::Last),  Some(0));
+        assert_eq!(fw.rank_query_with(2, Tie::First), Some(1));
+        assert_eq!(fw.rank_query_with(2, Tie::Last),  Some(3));
+        assert_eq!(fw.rank_query_with(4, Tie::First), Some(1));
+        assert_eq!(fw.rank_query_with(5, Tie::First), Some(4));
+        assert_eq!(fw.rank_query_with(5, Tie::Last),  Some(7));
+        assert_eq!(fw.rank_query_with(9, Tie::First), Some(8));
+        assert_eq!(fw.rank_query_with(9, Tie::Last),  Some(8));
+        assert_eq!(fw.rank_query_with(5, Tie::Last),  Some(fw.rank_query(5)));
+
+        let fw = Fenwick::from_vec(vec![3_u32, 0, 0, 1, 0]);
+        assert_eq!(fw.rank_query_with(2, Tie::First), None);
+        assert_eq!(fw.rank_query_with(3, Tie::First), Some(0));
+        assert_eq!(fw.rank_query_with(3, Tie::Last),  Some(2));
+    }
+
+    #[test]
+    fn first_last_nonzero() {
+        let fw = Fenwick::from_vec(vec![0, 0, 3, 0, -1, 4, 0, 0, 0]);
+        assert_eq!(fw.first_nonzero(), Some(2));
+        assert_eq!(fw.last_nonzero(),  Some(5));
+
+        let fw = Fenwick::from_vec(vec![7, 0, 0, 0, 0]);
+        assert_eq!(fw.first_nonzero(), Some(0));
+        assert_eq!(fw.last_nonzero(),  Some(0));
+
+        let fw = Fenwick::<i32>::new(8);
+        assert_eq!(fw.first_nonzero(), None);
+        assert_eq!(fw.last_nonzero(),  None);
+    }
+
+    #[test]
+    fn bulk_set() {
+        let mut fw = Fenwick::from_vec((1..=17).collect::<Vec<i32>>());
+        fw.bulk_set(&[(3, 0), (10, 5), (3, 7)]);
+        assert_eq!(fw.get(3),  7);
+        assert_eq!(fw.get(10), 5);
+        assert_eq!(fw.total(), 153 - 4 + 7 - 11 + 5);
+
+        // Enough assignments to take the rebuilding path.
+        let pairs = (0..17).map(|i| (i, 2)).chain([(4, 9), (4, 1)]);
+        let pairs = pairs.collect::<Vec<_>>();
+        fw.bulk_set(&pairs);
+        for i in 0..17 {
+            let expect = if i < 4 { 2 * (i + 1) } else { 2 * i + 1 };
+            assert_eq!(fw.prefix_sum(i as usize), expect);
+        }
+        assert!(fw.validate());
+    }
+
+    #[test]
+    fn try_from_raw_nodes() {
+        let fw    = Fenwick::from_vec(vec![3, 1, 4, 1, 5, 9, 2, 6, 5]);
+        let nodes = fw.data.clone();
+        let fw2   = Fenwick::try_from_raw_nodes(nodes.clone()).unwrap();
+        assert_eq!(fw2.decumulate(), fw.decumulate());
+
+        let truncated = nodes[..8].to_vec();
+        assert_eq!(Fenwick::try_from_raw_nodes(truncated).unwrap_err(),
+                   FenwickError::InvalidNodes { len: 8 });
+        assert!(Fenwick::<i32>::try_from_raw_nodes(vec![1]).is_err());
+
+        let mut nodes = Fenwick::from_vec(vec![1.0, 2.0, 3.0, 4.0, 5.0]).data;
+        nodes[2] = f64::NAN;
+        assert_eq!(Fenwick::try_from_raw_nodes(nodes).unwrap_err(),
+                   FenwickError::InvalidNodes { len: 9 });
+    }
+
+    #[test]
+    fn shift_values() {
+        let mut fw  = Fenwick::from_vec(vec![4, -2, 7, 0, 1, 3, -5, 8, 2]);
+        let before  = fw.prefix_sums_vec();
+        fw.shift_values(3);
+        for (i, sum) in before.into_iter().enumerate() {
+            assert_eq!(fw.prefix_sum(i), sum + (i as i32 + 1) * 3);
+        }
+        assert_eq!(fw.get(1), 1);
+    }
+
+    /// Returns a small xorshift generator for the sampling tests.
+    ///
+    fn xorshift(mut state: u64) -> impl FnMut() -> u64 {
+        move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        }
+    }
+
+    #[test]
+    fn weighted_sample() {
+        let fw      = Fenwick::from_vec(vec![1_u64, 0, 3, 0, 6]);
+        let mut rng = xorshift(0x2545_f491_4f6c_dd1d);
+        let mut hit = [0; 5];
+        for _ in 0..10_000 {
+            hit[fw.weighted_sample(&mut rng).unwrap()] += 1;
+        }
+        assert_eq!(hit[1], 0);
+        assert_eq!(hit[3], 0);
+        assert!((800..1200).contains(&hit[0]));
+        assert!((2700..3300).contains(&hit[2]));
+        assert!((5700..6300).contains(&hit[4]));
+
+        let fw = Fenwick::<u64>::new(4);
+        assert_eq!(fw.weighted_sample(&mut rng), None);
+    }
+
+    #[test]
+    fn weighted_sample_n() {
+        let fw      = Fenwick::from_vec(vec![2_u64, 0, 5, 3, 0]);
+        let mut rng = xorshift(0x9e37_79b9_7f4a_7c15);
+        let draws   = fw.weighted_sample_n(10_000, &mut rng);
+        assert_eq!(draws.len(), 10_000);
+
+        let mut hit = [0; 5];
+        for i in draws {
+            hit[i] += 1;
+        }
+        assert_eq!(hit[1] + hit[4], 0);
+        assert!((1800..2200).contains(&hit[0]));
+        assert!((4700..5300).contains(&hit[2]));
+        assert!((2700..3300).contains(&hit[3]));
+
+        let fw = Fenwick::<u64>::new(4);
+        assert!(fw.weighted_sample_n(3, &mut rng).is_empty());
+    }
+
+    #[test]
+    fn prefix_range() {
+        let fw = Fenwick::from_vec(vec![5, -3, 8, 2, 0, 7, -1, 4, 6]);
+        for start in 0..9 {
+            for end in start..9 {
+                let expect = fw.prefix_sums().skip(start).take(end - start + 1);
+                assert!(fw.prefix_range(start, end).eq(expect));
+            }
+        }
+    }
+
+    /// Fails to compile if any of the tree types stops being `Send + Sync`
+    /// for `Send + Sync` elements, e.g. by gaining an `Rc` or `Cell` field.
+    ///
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn send_sync() {
+        assert_send_sync::<Fenwick<i64>>();
+        assert_send_sync::<CachedFenwick<i64>>();
+        assert_send_sync::<CompressedFenwick<i64>>();
+        assert_send_sync::<ElementGuard<'_, i64>>();
+        assert_send_sync::<FenwickIter<'_, i64>>();
+        assert_send_sync::<FenwickMod<7>>();
+        assert_send_sync::<FenwickProduct<f64>>();
+        assert_send_sync::<SparseFenwick<i32>>();
+        assert_send_sync::<FrozenCachedFenwick<i32>>();
+        assert_send_sync::<SignedRankFenwick>();
+        assert_send_sync::<FenwickND<i64, 3>>();
+        assert_send_sync::<FenwickOneBased<i64>>();
+        assert_send_sync::<FenwickView<i64>>();
+        assert_send_sync::<FrozenFenwick<i64>>();
+        assert_send_sync::<RankCursor<'_, i64>>();
+        assert_send_sync::<SlidingWindow<i64>>();
+    }
+
+    #[test]
+    fn from_cow() {
+        let values = [2, 7, 1, 8, 2, 8, 1, 8, 3];
+        let fw     = Fenwick::from_cow(Cow::Borrowed(&values[..]));
+        let fw2    = Fenwick::from(&values[..]);
+        assert_eq!(fw.prefix_sums_vec(), fw2.prefix_sums_vec());
+
+        let mut vec = Vec::with_capacity(17);
+        vec.extend_from_slice(&values);
+        let ptr = vec.as_ptr();
+        let fw  = Fenwick::from_cow(Cow::Owned(vec));
+        assert_eq!(fw.data.as_ptr(), ptr);
+        assert_eq!(fw.total(), 40);
+    }
+
+    #[test]
+    fn update_many_sorted() {
+        let mut updates = vec![(7, 3), (2, -1), (7, 4), (0, 5), (2, 6), (4, 1)];
+        let mut fw1     = Fenwick::<i32>::new(9);
+        let mut fw2     = Fenwick::<i32>::new(9);
+        for &(idx, delta) in &updates {
+            fw1.add(idx, delta);
+        }
+        fw2.update_many_sorted(&mut updates);
+        assert_eq!(updates, [(0, 5), (2, 5), (4, 1), (7, 7)]);
+        assert_eq!(fw2.prefix_sums_vec(), fw1.prefix_sums_vec());
+    }
+
+    #[test]
+    #[cfg(feature = "checked-build")]
+    #[should_panic(expected = "overflow")]
+    fn checked_build_overflow() {
+        let _ = Fenwick::from_vec(vec![0_u8, 100, 100, 60, 1]);
+    }
+
+    #[test]
+    fn estimate_memory() {
+        let small = Fenwick::<u64>::new(8);
+        let large = Fenwick::<u64>::new(1000);
+        assert!(small.estimate_memory() >= 9 * 8);
+        assert!(large.estimate_memory() >= 1025 * 8);
+        assert!(large.estimate_memory() > small.estimate_memory());
+    }
+
+    #[test]
+    fn iter_nth_last() {
+        let mut fw = Fenwick::from_vec(vec![3, 1, 4, 1, 5, 9, 2, 6]);
+        fw.add(8, 5);
+        let mut it = fw.iter();
+        assert_eq!(it.nth(3), Some(1));
+        assert_eq!(it.next(), Some(5));
+        assert_eq!(it.nth(1), Some(2));
+        assert_eq!(it.nth(5), None);
+        assert_eq!(it.next(), None);
+        
+        assert_eq!(fw.iter().last(), fw.values().take(8).last());
+        assert_eq!(fw.iter().nth(7), Some(6));
+        assert_eq!(fw.iter().nth(8), None);
+    }
+
+    #[test]
+    fn iter_count() {
+        let mut fw = Fenwick::from_vec(vec![3, 1, 4, 1, 5, 9, 2, 6]);
+        fw.add(8, 5);
+        assert_eq!(fw.iter().count(), fw.values().take(8).count());
+
+        let mut it = fw.iter();
+        it.next();
+        it.next();
+        assert_eq!(it.size_hint(), (6, Some(6)));
+        assert_eq!(it.count(), 6);
+
+        let mut it = fw.iter();
+        it.nth(10);
+        assert_eq!(it.size_hint(), (0, Some(0)));
+        assert_eq!(it.count(), 0);
+        assert_eq!(fw.iter().skip(8).last(), None);
+    }
+
+    #[test]
+    fn has_prefix_sum() {
+        let fw = Fenwick::from_vec(vec![2_u32, 0, 3, 1, 0, 4, 0, 0, 5]);
+        for value in [2, 5, 6, 10, 15] {
+            assert!(fw.has_prefix_sum(value), "{}", value);
+        }
+        for value in [0, 1, 3, 4, 7, 9, 11, 16] {
+            assert!(!fw.has_prefix_sum(value), "{}", value);
+        }
+    }
+
+    #[test]
+    fn modes() {
+        let mut fw = Fenwick::<i32>::new_mode(9, Mode::RangeUpdatePointQuery);
+        fw.range_add(2, 5, 3);
+        fw.range_add(4, 8, -1);
+        fw.range_add(0, 0, 7);
+        let expect = [7, 0, 3, 3, 2, 2, -1, -1, -1];
+        for (i, &value) in expect.iter().enumerate() {
+            assert_eq!(fw.point_get(i), value);
+        }
+        assert_eq!(fw.mode(), Mode::RangeUpdatePointQuery);
+
+        let mut fw = Fenwick::<i32>::new(9);
+        fw.add(3, 4);
+        assert_eq!(fw.mode(), Mode::PointUpdateRangeQuery);
+        assert_eq!(fw.prefix_sum(8), 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "RangeUpdatePointQuery")]
+    fn range_add_wrong_mode() {
+        let mut fw = Fenwick::<i32>::new(8);
+        fw.range_add(0, 3, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "RangeUpdatePointQuery")]
+    fn point_get_wrong_mode() {
+        let fw = Fenwick::from_vec(vec![1, 2, 3]);
+        fw.point_get(1);
+    }
+
+    #[test]
+    fn bracket() {
+        // Prefix sums: 2, 2, 5, 6, 6, 10, 10, 10, 15
+        let mut fw = Fenwick::from_vec(vec![2_u32, 0, 3, 1, 0, 4, 0, 0]);
+        fw.add(8, 5);
+        assert_eq!(fw.bracket(1),  (None,    Some(0)));
+        assert_eq!(fw.bracket(2),  (Some(1), Some(0)));
+        assert_eq!(fw.bracket(3),  (Some(1), Some(2)));
+        assert_eq!(fw.bracket(6),  (Some(4), Some(3)));
+        assert_eq!(fw.bracket(8),  (Some(4), Some(5)));
+        assert_eq!(fw.bracket(12), (Some(7), Some(8)));
+        assert_eq!(fw.bracket(15), (Some(8), Some(8)));
+        assert_eq!(fw.bracket(16), (Some(8), None));
+        for value in 0..17 {
+            let (lo, hi) = fw.bracket(value);
+            if let Some(lo) = lo {
+                assert_eq!(lo, fw.rank_query(value));
+            }
+            if let Some(hi) = hi {
+                assert_eq!(hi, fw.min_rank_query(value));
+            }
+        }
+    }
+
+    #[test]
+    fn from_prefix_sums() {
+        let fw = Fenwick::from_prefix_sums(&[1, 2, 5, 6]);
+        assert_eq!(fw.decumulate(), [1, 1, 3, 1, 0]);
+        assert_eq!(fw.prefix_sums_vec(), [1, 2, 5, 6, 6]);
+
+        let sums = [4, 1, 1, 9, -2, 0, 3, 3, 8];
+        let fw   = Fenwick::from_prefix_sums(&sums);
+        assert_eq!(fw.prefix_sums_vec()[..9], sums);
+    }
+
+    #[test]
+    fn element_range() {
+        let fw = Fenwick::from_vec(vec![5, -3, 8, 2, 0, 7, -1, 4, 6]);
+        for start in 0..=9 {
+            for end in start..=9 {
+                let expect = (start..end).map(|i| fw.get(i));
+                assert!(fw.element_range(start, end).eq(expect));
+            }
+        }
+    }
+
+    #[test]
+    fn checked_range_sum() {
+        let fw = Fenwick::from_vec(vec![3, 1, 4, 1]);
+        assert_eq!(fw.checked_range_sum(1, 4), Ok(6));
+        assert_eq!(fw.checked_range_sum(0, 5), Ok(9));
+        assert_eq!(fw.checked_range_sum(1, 4), Ok(fw.range_sum(1, 3)));
+        
+        // Empty ranges sum to zero, including one past the last element.
+        assert_eq!(fw.checked_range_sum(2, 2), Ok(0));
+        assert_eq!(fw.checked_range_sum(5, 5), Ok(0));
+        
+        assert_eq!(fw.checked_range_sum(3, 2),
+                   Err(FenwickError::InvalidRange { start: 3, end: 2 }));
+        assert_eq!(fw.checked_range_sum(1, 6),
+                   Err(FenwickError::IndexOutOfBounds { idx: 6, end: 4 }));
+        assert_eq!(fw.checked_range_sum(6, 6),
+                   Err(FenwickError::IndexOutOfBounds { idx: 6, end: 4 }));
+    }
+
+    #[test]
+    fn generic_index() {
+        let mut fw = Fenwick::from_vec(vec![3, 1, 4, 1]);
+        fw.add(4, 5);
+        assert_eq!(fw.prefix_sum_at(2_u32), Ok(8));
+        assert_eq!(fw.get_at(4_u8), Ok(5));
+        assert_eq!(fw.add_at(1_u16, 2), Ok(()));
+        assert_eq!(fw.set_at(0_i64, 7), Ok(()));
+        assert_eq!(fw.prefix_sum(4), 20);
+
+        assert_eq!(fw.get_at(-1_i32), Err(FenwickError::InvalidIndex));
+        assert_eq!(fw.prefix_sum_at(5_u32),
+                   Err(FenwickError::IndexOutOfBounds { idx: 5, end: 4 }));
+        assert_eq!(fw.add_at(9_u64, 1),
+                   Err(FenwickError::IndexOutOfBounds { idx: 9, end: 4 }));
+        assert_eq!(fw.total(), 20);
+    }
+
+    #[test]
+    fn to_from_bytes() {
+        let mut fw = Fenwick::from_vec(vec![3_u64, 1, 4, 1, 5, 9, 2, 6]);
+        fw.add(8, 5);
+        let bytes = fw.to_bytes();
+        assert_eq!(&bytes[..5], b"FWK2\0");
+        assert_eq!(bytes.len(), 13 + 9 * 8);
+        
+        let fw2 = Fenwick::from_bytes(&bytes).unwrap();
+        assert_eq!(fw2.prefix_sums_vec(), fw.prefix_sums_vec());
+        assert_eq!(fw2, fw);
+        
+        let mut ru = Fenwick::<u64>::new_mode(8, Mode::RangeUpdatePointQuery);
+        ru.range_add(2, 8, 4);
+        ru.range_add(5, 8, 1);
+        let ru_bytes = ru.to_bytes();
+        assert_eq!(ru_bytes[4], 1);
+        let ru2 = Fenwick::from_bytes(&ru_bytes).unwrap();
+        assert_eq!(ru2.mode(), Mode::RangeUpdatePointQuery);
+        assert_eq!(ru2, ru);
+        assert_eq!(ru2.point_get(6), 5);
+        
+        let mut bad = bytes.clone();
+        bad[0] = b'X';
+        assert_eq!(Fenwick::from_bytes(&bad).unwrap_err(),
+                   FenwickError::InvalidEncoding);
+        let mut bad = bytes.clone();
+        bad[4] = 2;
+        assert_eq!(Fenwick::from_bytes(&bad).unwrap_err(),
+                   FenwickError::InvalidEncoding);
+        assert!(Fenwick::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(Fenwick::from_bytes(&bytes[..8]).is_err());
+    }
+
+    #[test]
+    fn from_sorted_merge() {
+        let a = [1, 3, 3, 7, 9];
+        let b = [0, 3, 4, 9, 9, 12];
+        let (fw, keys) = Fenwick::from_sorted_merge(&a, &b);
+        
+        let mut merged = a.iter().chain(&b).copied().collect::<Vec<_>>();
+        merged.sort_unstable();
+        let mut unique = merged.clone();
+        unique.dedup();
+        assert_eq!(keys, unique);
+        for (i, key) in keys.iter().enumerate() {
+            let count = merged.iter().filter(|&k| k == key).count() as u64;
+            assert_eq!(fw.get(i), count);
+        }
+        assert_eq!(fw.total(), 11);
+        
+        let (fw, keys) = Fenwick::<u64>::from_sorted_merge::<i32>(&[], &[]);
+        assert!(keys.is_empty());
+        assert_eq!(fw.total(), 0);
+    }
+
+    #[test]
+    fn take_total() {
+        let mut fw = Fenwick::<u32>::new(8);
+        fw.add(1, 4);
+        fw.add(6, 2);
+        assert_eq!(fw.take_total(), 6);
+        assert_eq!(fw.total(), 0);
+        
+        fw.add(0, 3);
+        fw.add(8, 1);
+        assert_eq!(fw.take_total(), 4);
+        assert_eq!(fw.take_total(), 0);
+        assert_eq!(fw.end(), 8);
+    }
+
+    #[test]
+    fn diff_update() {
+        let mut fw  = Fenwick::from_vec(vec![3_u32, 1, 4, 1, 5, 9, 2, 6]);
+        let old     = fw.values().collect::<Vec<_>>();
+        let mut new = old.clone();
+        new[1] = 0;
+        new[3] = 7;
+        new[7] = 1;
+        new[8] = 8;
+        fw.diff_update(&old, &new);
+        assert_eq!(fw.values().collect::<Vec<_>>(), new);
+        assert_eq!(fw.total(), 39);
+    }
+
+    #[test]
+    fn sum_excluding() {
+        let values = [3, 1, 4, 1, 5, 9, 2, 6, 5];
+        let fw     = Fenwick::from(&values[..]);
+        for idx in 0..9 {
+            let expect = values.iter().sum::<i32>() - values[idx];
+            assert_eq!(fw.sum_excluding(idx), expect);
+        }
+    }
+
+    #[test]
+    fn split_range_sum() {
+        let values = [3, -1, 4, 1, -5, 9, 2, 6, 5];
+        let fw     = Fenwick::from(&values[..]);
+        for start in 0..9 {
+            for end in start + 1..9 {
+                for pivot in start..end {
+                    let (left, right) = fw.split_range_sum(start, pivot, end);
+                    assert_eq!(left,  fw.range_sum(start, pivot));
+                    assert_eq!(right, fw.range_sum(pivot + 1, end));
+                    assert_eq!(left + right, fw.range_sum(start, end));
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn split_range_sum_pivot_at_end() {
+        let fw = Fenwick::from(&[1, 2, 3, 4][..]);
+        fw.split_range_sum(1, 3, 3);
+    }
+
+    #[test]
+    fn rank_query_with_remainder() {
+        // Prefix sums: 2, 2, 5, 6, 6, 10, 10, 10, 15
+        let mut fw = Fenwick::from_vec(vec![2_u32, 0, 3, 1, 0, 4, 0, 0]);
+        fw.add(8, 5);
+        assert_eq!(fw.rank_query_with_remainder(1),  None);
+        assert_eq!(fw.rank_query_with_remainder(2),  Some((1, 0)));
+        assert_eq!(fw.rank_query_with_remainder(4),  Some((1, 2)));
+        assert_eq!(fw.rank_query_with_remainder(8),  Some((4, 2)));
+        assert_eq!(fw.rank_query_with_remainder(13), Some((7, 3)));
+        assert_eq!(fw.rank_query_with_remainder(20), Some((8, 5)));
+    }
+
+    #[test]
+    fn with_default_fn() {
+        use std::ops::{Add, Sub, AddAssign, SubAssign};
+
+        // A newtype with the arithmetic traits but no `Default`.
+        #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+        struct Cents(i64);
+
+        impl Add for Cents {
+            type Output = Cents;
+            fn add(self, rhs: Cents) -> Cents { Cents(self.0 + rhs.0) }
+        }
+        impl Sub for Cents {
+            type Output = Cents;
+            fn sub(self, rhs: Cents) -> Cents { Cents(self.0 - rhs.0) }
+        }
+        impl AddAssign for Cents {
+            fn add_assign(&mut self, rhs: Cents) { self.0 += rhs.0; }
+        }
+        impl SubAssign for Cents {
+            fn sub_assign(&mut self, rhs: Cents) { self.0 -= rhs.0; }
+        }
+        
+        let mut fw = Fenwick::with_default_fn(8, || Cents(0));
+        fw.add(0, Cents(250));
+        fw.add(3, Cents(100));
+        fw.add(7, Cents(5));
+        fw.sub(3, Cents(40));
+        assert_eq!(fw.end(), 8);
+        assert_eq!(fw.prefix_sum(2), Cents(250));
+        assert_eq!(fw.prefix_sum(6), Cents(310));
+        assert_eq!(fw.total(), Cents(315));
+    }
+
+    #[test]
+    fn range_sum_from_zero() {
+        let values  = (0..33).map(|n| (n * 37) % 11 - 5).collect::<Vec<i32>>();
+        let fw      = Fenwick::from_vec(values.clone());
+        let mut sum = 0;
+        for (k, value) in values.into_iter().enumerate() {
+            sum += value;
+            assert_eq!(fw.range_sum(0, k), fw.prefix_sum(k));
+            assert_eq!(fw.range_sum(0, k), sum);
+        }
+    }
+
+    #[test]
+    fn try_from_iter() {
+        let fw = Fenwick::try_from_iter("3 1 4 1 5".split(' ').map(str::parse));
+        assert_eq!(fw.map(|fw| fw.total()), Ok(14));
+        
+        let mut parsed = 0;
+        let items = "3 1 x 1 5".split(' ').map(|s| {
+            parsed += 1;
+            s.parse::<i32>()
+        });
+        assert!(Fenwick::try_from_iter(items).is_err());
+        assert_eq!(parsed, 3);
+    }
+
+    #[test]
+    fn nth_prefix() {
+        let mut fw = Fenwick::from_vec(vec![3, 1, 4, 1]);
+        fw.add(4, 5);
+        assert_eq!(fw.nth_prefix(0), Some(3));
+        assert_eq!(fw.nth_prefix(2), Some(8));
+        assert_eq!(fw.nth_prefix(4), Some(14));
+        assert_eq!(fw.nth_prefix(5), None);
+        assert_eq!(fw.nth_prefix(usize::MAX), None);
+        assert_eq!(fw.nth_prefix(3), fw.prefix_sums().nth(3));
+    }
+
+    #[test]
+    fn split_value_at() {
+        let mut fw = Fenwick::from_vec(vec![10_u32, 0, 6, 0, 4]);
+        fw.split_value_at(0, 1, 3);
+        fw.split_value_at(2, 3, 6);
+        fw.split_value_at(4, 0, 1);
+        assert_eq!(fw.decumulate()[..5], [8, 3, 0, 6, 3]);
+        assert_eq!(fw.total(), 20);
+    }
+
+    #[test]
+    #[should_panic(expected = "more than")]
+    fn split_value_at_too_much() {
+        let mut fw = Fenwick::from_vec(vec![10_u32, 0, 6, 0, 4]);
+        fw.split_value_at(2, 3, 7);
+    }
+
+    #[test]
+    #[should_panic(expected = "within the tree")]
+    fn split_value_at_past_end() {
+        let mut fw = Fenwick::from_vec(vec![10_u32, 0, 6, 0, 4]);
+        fw.split_value_at(0, 9, 1);
+    }
+
+    #[test]
+    fn suffix_rank_query() {
+        // Suffix sums: 15, 13, 13, 10, 9, 9, 5, 5, 5
+        let mut fw = Fenwick::from_vec(vec![2_u32, 0, 3, 1, 0, 4, 0, 0]);
+        fw.add(8, 5);
+        assert_eq!(fw.suffix_rank_query(0),  Some(8));
+        assert_eq!(fw.suffix_rank_query(5),  Some(8));
+        assert_eq!(fw.suffix_rank_query(6),  Some(5));
+        assert_eq!(fw.suffix_rank_query(9),  Some(5));
+        assert_eq!(fw.suffix_rank_query(10), Some(3));
+        assert_eq!(fw.suffix_rank_query(13), Some(2));
+        assert_eq!(fw.suffix_rank_query(14), Some(0));
+        assert_eq!(fw.suffix_rank_query(15), Some(0));
+        assert_eq!(fw.suffix_rank_query(16), None);
         
-        assert_eq!(fw.prefix_sum(fw.end()), 3);
-        fw.add(fw.end(), 3);
-        assert_eq!(fw.prefix_sum(fw.end()), 6);
-        assert_eq!(fw.prefix_sum(fw.end() - 1), 3);
+        // Reversing the values turns suffix sums into prefix sums.
+        let mut rev = Fenwick::from_vec(vec![5_u32, 0, 0, 4, 0, 1, 3, 0]);
+        rev.add(8, 2);
+        for value in 1..=15 {
+            let i = fw.suffix_rank_query(value).unwrap();
+            assert_eq!(8 - i, rev.min_rank_query(value));
+        }
     }
-    
+
     #[test]
-    fn sub() {
-        let mut fw = Fenwick::new(8);
-        fw.add(1, 1);
-        fw.add(2, 1);
-        fw.add(2, 1);
-        fw.sub(2, 1);
-        assert_eq!(fw.prefix_sum(2), 2);
+    fn batch_range_sum_sorted() {
+        let fw = Fenwick::from_vec((0..17).map(|n| (n * 5) % 7).collect());
+        let queries = [(3, 9), (0, 16), (5, 5), (0, 0), (10, 12), (2, 14)];
+        let sums    = fw.batch_range_sum_sorted(&queries);
+        let naive   = queries.iter().map(|&(i, j)| fw.range_sum(i, j));
+        assert!(sums.into_iter().eq(naive));
+        assert!(fw.batch_range_sum_sorted(&[]).is_empty());
     }
-    
-    #[test] 
-    fn prefix_sum() {
-        let mut fw = Fenwick::new(8);
-        fw.add(1, 1);  // sum = 2
-        fw.add(0, 1);  // sum = 1
-        fw.add(3, 1);  // sum = 3
-        assert_eq!(fw.prefix_sum(3), 3);
-        
-        fw.sub(0, 1);
-        assert_eq!(fw.prefix_sum(3), 2);
+
+    #[test]
+    fn singleton() {
+        let fw = Fenwick::singleton(8, 3, 5);
+        for i in 0..=fw.end() {
+            assert_eq!(fw.get(i), if i == 3 { 5 } else { 0 });
+        }
+        assert_eq!(fw.total(), 5);
+        assert_eq!(Fenwick::singleton(1, 0, 2.5).get(0), 2.5);
     }
-    
+
     #[test]
-    fn get() {
-        let mut fw = Fenwick::new(8);
-        fw.add(0, 5);
-        fw.add(1, 4);
-        assert_eq!(fw.get(1), 4);
-        assert_eq!(fw.get(0), 5);
-        assert_eq!(fw.get(fw.end()), 0);
+    #[should_panic(expected = "past the size")]
+    fn singleton_out_of_range() {
+        Fenwick::singleton(8, 8, 1);
     }
-    
+
     #[test]
-    fn set() {
-        let mut fw = Fenwick::new(8);
-        fw.add(0, 5);
-        fw.add(1, 4);
-        assert_eq!(fw.get(1), 4);
-        assert_eq!(fw.get(0), 5);
+    #[cfg(feature = "debug-invariants")]
+    #[should_panic(expected = "invariant violated: 8 nodes for size 9")]
+    fn invariants_truncated() {
+        let mut fw = Fenwick::from_vec(vec![3, 1, 4, 1, 5, 9, 2, 6]);
+        fw.data.pop();
+        fw.add(0, 1);
+    }
+
+    #[test]
+    #[cfg(feature = "debug-invariants")]
+    #[should_panic(expected = "invariant violated: node 3 is NaN")]
+    fn invariants_nan() {
+        let mut fw = Fenwick::from_vec(vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+        fw.data[3] = f64::NAN;
+        fw.sub(0, 1.0);
+    }
+
+    #[test]
+    fn decumulate_round_trip() {
+        let fw = Fenwick::from_vec(vec![3, -1, 4, 1, -5, 9, 2, 6, 5, 3, 5]);
+        assert_eq!(Fenwick::from_vec(fw.decumulate()), fw);
+        assert_eq!(Fenwick::from(fw.decumulate()), fw);
         
-        fw.set(1, 0);
-        assert_eq!(fw.get(1), 0);
-        assert_eq!(fw.prefix_sum(8), 5);
+        let values = fw.decumulate();
+        assert!(fw.values().eq(values.iter().copied()));
+        assert!(fw.into_values().eq(values));
+    }
+
+    #[test]
+    fn raw_paths() {
+        let mut fw1 = Fenwick::from_vec(vec![3, 1, 4, 1, 5, 9, 2, 6, 5]);
+        let mut fw2 = fw1.clone();
+        for (idx, delta) in [(0, 2), (3, -4), (8, 7), (5, 1)] {
+            fw1.add(idx, delta);
+            fw2.add_raw(idx, delta);
+            fw1.sub(8 - idx, delta);
+            fw2.sub_raw(8 - idx, delta);
+        }
+        assert_eq!(fw1, fw2);
+        for idx in 0..9 {
+            assert_eq!(fw1.prefix_sum(idx), fw2.prefix_sum_raw(idx));
+        }
+    }
+
+    #[test]
+    fn eq_ignores_padding() {
+        let short = Fenwick::from_vec(vec![1, 2, 3]);
+        let long  = Fenwick::from_vec(vec![1, 2, 3, 0, 0, 0, 0, 0, 0]);
+        assert_ne!(short.end(), long.end());
+        assert_eq!(short, long);
+        assert_eq!(long, short);
         
-        assert_eq!(fw.get(0), 5);
-        fw.set(0, 4);
-        assert_eq!(fw.get(0), 4);
+        let other = Fenwick::from_vec(vec![1, 2, 3, 0, 0, 0, 0, 0, 4]);
+        assert_ne!(short, other);
+        assert_ne!(other, short);
+        assert_ne!(short, Fenwick::from_vec(vec![1, 2, 4]));
         
-        fw.set(fw.end(), 3);
-        assert_eq!(fw.get(fw.end()), 3);
-        assert_eq!(fw.prefix_sum(fw.end()), 7);
-        assert_eq!(fw.total(), 7);
+        let diff = Fenwick::<i32>::new_mode(3, Mode::RangeUpdatePointQuery);
+        assert_ne!(Fenwick::new(3), diff);
+    }
+
+    #[test]
+    fn try_new() {
+        let fw = Fenwick::<i32>::try_new(5).unwrap();
+        assert_eq!(fw, Fenwick::new(5));
+        assert_eq!(fw.end(), 8);
         
-        fw.set(0, 8);
-        assert_eq!(fw.get(0), 8);
-        fw.set(0, 0);
-        assert_eq!(fw.get(0), 0);
+        // Padding usize::MAX overflows, and usize::MAX / 2 elements overflow
+        // the maximum allocation size. Neither may abort the process.
+        assert_eq!(Fenwick::<u64>::try_new(usize::MAX),
+                   Err(FenwickError::AllocationFailed { size: usize::MAX }));
+        assert_eq!(Fenwick::<u64>::try_new(usize::MAX / 2),
+                   Err(FenwickError::AllocationFailed { 
+                       size: usize::MAX / 2 
+                   }));
     }
-    
+
     #[test]
-    fn range_sum() {
-        let mut fw = Fenwick::new(8);
-        fw.add(0, 1);  // sum = 1
-        fw.add(1, 1);  // sum = 2 
-        fw.add(2, 3);  // sum = 5
-        fw.add(3, 1);  // sum = 6
-        fw.add(4, 1);  // sum = 7
+    fn concat() {
+        let mut a = Fenwick::from_vec(vec![3, 1, 4, 1]);
+        a.add(4, 5);
+        let b     = Fenwick::from_vec(vec![9, 2, 6]);
+        let fw    = a.concat(&b);
+        assert_eq!(fw.end(), 16);
         
-        assert_eq!(fw.range_sum(1, 3), 5);
-        assert_eq!(fw.range_sum(0, 3), 6);
+        let expect = [3, 1, 4, 1, 5, 9, 2, 6, 0, 0];
+        assert_eq!(fw.decumulate()[..10], expect);
+        for i in 0..=4 {
+            assert_eq!(fw.range_sum(0, i), a.prefix_sum(i));
+        }
+        for i in 0..=2 {
+            assert_eq!(fw.range_sum(5, 5 + i), b.prefix_sum(i));
+        }
+        // Ranges spanning the boundary between the two trees.
+        assert_eq!(fw.range_sum(3, 6), 17);
+        assert_eq!(fw.range_sum(4, 5), 14);
+        assert_eq!(fw.range_sum(0, 16), a.total() + b.total());
         
-        assert_eq!(fw.range_sum(2, 4), 5);
-        assert_eq!(fw.range_sum(0, 0), 1);
-        assert_eq!(fw.range_sum(2, 2), 3);
+        let empty = Fenwick::<i32>::empty();
+        assert_eq!(b.concat(&empty).decumulate()[..3], [9, 2, 6]);
     }
-    
-    #[test] 
-    fn range_sum2() {
-        let mut fw = Fenwick::new(8);
-        fw.add(0, 1);  // sum = 1
-        fw.add(1, 1);  // sum = 2 
-        fw.add(2, 3);  // sum = 5
-        fw.add(3, 1);  // sum = 6
-        fw.add(4, 1);  // sum = 7
-        
-        assert_eq!(fw.range_sum2(1, 3), 4);
-        assert_eq!(fw.range_sum2(0, 3), 5);
+
+    #[test]
+    fn sum_of_prefix_sums() {
+        let fw    = Fenwick::from_vec(vec![3, -1, 4, 1, -5, 9, 2, 6, 5]);
+        let naive = (0..=fw.end()).map(|i| fw.prefix_sum(i)).sum::<i32>();
+        assert_eq!(fw.sum_of_prefix_sums(), naive);
         
-        assert_eq!(fw.range_sum2(2, 4), 4);
-        assert_eq!(fw.range_sum2(0, 0), 0);
-        assert_eq!(fw.range_sum2(2, 2), 0);
+        let weighted = fw.values().enumerate()
+                         .map(|(i, v)| (fw.end() + 1 - i) as i32 * v)
+                         .sum::<i32>();
+        assert_eq!(fw.sum_of_prefix_sums(), weighted);
+        assert_eq!(Fenwick::<f64>::new(4).sum_of_prefix_sums(), 0.0);
     }
 
     #[test]
-    fn rank_query() {
-        let mut fw = Fenwick::new(8);
-        fw.add(0, 1);  // sum = 1
-        fw.add(1, 1);  // sum = 2 
-        fw.add(2, 3);  // sum = 5
-        fw.add(3, 1);  // sum = 6
-        fw.add(4, 1);  // sum = 7
+    fn add_with() {
+        let mut fw = Fenwick::<i64>::new(8);
+        let deltas: [i32; 3] = [i32::MAX, i32::MAX, -7];
+        fw.add_with(1, deltas[0]);
+        fw.add_with(1, deltas[1]);
+        fw.add_with(4, deltas[2]);
+        fw.add_with(6, 3_u8);
+        assert_eq!(fw.get(1), 2 * i32::MAX as i64);
+        assert_eq!(fw.prefix_sum(4), 2 * i32::MAX as i64 - 7);
+        assert_eq!(fw.total(), 2 * i32::MAX as i64 - 4);
+    }
 
-        assert_eq!(fw.rank_query(0), 0);
-        assert_eq!(fw.rank_query(1), 0);
-        assert_eq!(fw.rank_query(2), 1);
-        assert_eq!(fw.rank_query(7), 8);
+    #[test]
+    fn hot_path_matches_naive() {
+        // Check the query and update paths against plain arrays for every
+        // index, across sizes with and without padding.
+        for len in [1, 2, 3, 5, 8, 9, 16, 17, 33] {
+            let mut values = (0..len as i64).map(|v| v * 7 % 11 - 5)
+                                            .collect::<Vec<_>>();
+            let mut fw     = Fenwick::from(&values[..]);
+            values.resize(fw.end() + 1, 0);
+            
+            for (i, v) in values.iter_mut().enumerate() {
+                let delta = i as i64 % 3 - 1;
+                fw.add(i, delta);
+                *v += delta;
+            }
+            fw.sub(fw.end(), 4);
+            *values.last_mut().unwrap() -= 4;
+            
+            let mut sum = 0;
+            for (i, &v) in values.iter().enumerate() {
+                sum += v;
+                assert_eq!(fw.get(i), v);
+                assert_eq!(fw.prefix_sum(i), sum);
+                for j in i..values.len() {
+                    let expect = values[i..=j].iter().sum::<i64>();
+                    assert_eq!(fw.range_sum(i, j), expect);
+                }
+            }
+            assert_eq!(fw.total(), sum);
+        }
+    }
+
+    /// Times a tight loop of queries and updates. Timings aren't reliable in
+    /// debug builds or on loaded machines, so this is ignored by default;
+    /// run it with `cargo test --release -- --ignored --nocapture`. The
+    /// thresholds are generous enough to only catch a hot path that has lost
+    /// its inlining or gained a per-node bounds check in a loop.
+    ///
+    #[test]
+    #[ignore]
+    fn hot_path_timing() {
+        use std::hint::black_box;
+        use std::time::Instant;
         
-        assert_eq!(fw.rank_query(5), 2);
-        assert_eq!(fw.rank_query(6), 3);
+        const LEN   : usize = 1 << 16;
+        const ROUNDS: usize = 64;
         
-        fw.set(0, 0);
-        assert_eq!(fw.rank_query(5), 3);
+        let mut fw = Fenwick::from_vec((0..LEN as u64).collect());
+        let start  = Instant::now();
+        let mut acc = 0_u64;
+        for _ in 0..ROUNDS {
+            for i in 0..LEN {
+                acc = acc.wrapping_add(black_box(&fw).prefix_sum(i));
+            }
+        }
+        let query_ns = start.elapsed().as_nanos() / (ROUNDS * LEN) as u128;
+        black_box(acc);
+        
+        let start = Instant::now();
+        for _ in 0..ROUNDS {
+            for i in 0..LEN {
+                black_box(&mut fw).add(i, 1);
+            }
+        }
+        let update_ns = start.elapsed().as_nanos() / (ROUNDS * LEN) as u128;
+        
+        println!("prefix_sum: {} ns/op, add: {} ns/op", query_ns, update_ns);
+        assert!(query_ns  < 200, "prefix_sum took {} ns/op", query_ns);
+        assert!(update_ns < 200, "add took {} ns/op", update_ns);
+        assert_eq!(fw.total(), (LEN * (LEN - 1) / 2 + ROUNDS * LEN) as u64);
     }
-    
+
     #[test]
-    fn min_rank_query() {
-        let mut fw = Fenwick::new(8);
-        fw.add(0, 1);  // sum = 1
-        fw.add(1, 1);  // sum = 2 
-        fw.add(2, 3);  // sum = 5
-        fw.add(3, 1);  // sum = 6
-        fw.add(4, 1);  // sum = 7
+    fn iter_nodes() {
+        let mut fw = Fenwick::from_vec((1..=8).collect::<Vec<i32>>());
+        fw.add(8, 9);
+        let nodes  = fw.iter_nodes().collect::<Vec<_>>();
+        assert_eq!(fw.end(), 8);
+        assert_eq!(nodes.len(), 9);
+        assert_eq!(nodes[0], (0, 1, 0..=0));
+        assert_eq!(nodes[1], (1, 2, 1..=1));
+        assert_eq!(nodes[4], (4, 14, 1..=4));
+        assert_eq!(nodes[6], (6, 13, 5..=6));
+        assert_eq!(nodes[8], (8, 44, 1..=8));
+        
+        for (i, node, covered) in nodes {
+            let (first, last) = covered.into_inner();
+            assert_eq!(last, i);
+            assert_eq!(node, fw.range_sum(first, last));
+        }
+    }
 
-        assert_eq!(fw.min_rank_query(1), 0);
+    #[test]
+    fn add_tree_at() {
+        let mut fw = Fenwick::from_vec(vec![1, 1, 1, 1, 1, 1, 1, 1]);
+        fw.add(8, 1);
+        let small  = Fenwick::from_vec(vec![5, -2, 7, 0]);
+        assert_eq!(small.end(), 4);
         
-        assert_eq!(fw.min_rank_query(3), 2);  // Check basic.
-        assert_eq!(fw.min_rank_query(8), 4);  // Check that it falls on min.
+        fw.add_tree_at(&small, 2);
+        assert_eq!(fw.decumulate(), [1, 1, 6, -1, 8, 1, 1, 1, 1]);
+        assert_eq!(fw.range_sum(2, 4), small.total() + 3);
+        assert_eq!(fw.total(), 19);
         
-        fw.add(7, 3);  // sum = 10
+        // The last position the small tree fits at.
+        fw.add_tree_at(&small, 4);
+        assert_eq!(fw.decumulate(), [1, 1, 6, -1, 13, -1, 8, 1, 1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "doesn't fit")]
+    fn add_tree_at_past_end() {
+        let mut fw = Fenwick::<i32>::new(8);
+        fw.add_tree_at(&Fenwick::from_vec(vec![1, 2, 3]), 7);
+    }
+
+    #[test]
+    fn prefix_sum_f64() {
+        let mut fw = Fenwick::from_vec(vec![3, -1, 4, 1, -5, 9, 2, 6, 5]);
+        for i in 0..=fw.end() {
+            assert_eq!(fw.prefix_sum_f64(i), fw.prefix_sum(i) as f64);
+        }
+        assert_eq!(fw.total_f64(), 24.0);
+        assert_eq!(fw.prefix_sum_f64(3) / fw.total_f64(), 0.2916666666666667);
         
-        assert_eq!(fw.min_rank_query(7), 4);  // Should fall to 4.
-        assert_eq!(fw.min_rank_query(8), 7);  // Should advance to 7.
+        // Node 8 and element 0 each fit in an i32, but their sum doesn't.
+        fw.set(0, i32::MAX);
+        fw.set(8, i32::MAX - 16);
+        assert_eq!(fw.total_f64(), 2.0 * i32::MAX as f64);
+        assert_eq!(fw.prefix_sum_f64(8), 2.0 * i32::MAX as f64);
+        assert_eq!(fw.prefix_sum_f64(0), i32::MAX as f64);
+    }
 
-        assert_eq!(fw.min_rank_query(10), 7);
-        assert_eq!(fw.min_rank_query(11), 7);
+    #[test]
+    #[should_panic(expected = "one delta per element")]
+    fn apply_delta_vec_wrong_length() {
+        let mut fw = Fenwick::<i32>::new(8);
+        fw.apply_delta_vec(&[1; 8]);
     }
 
     #[test]
-    fn iterators() {
-        let mut fw = Fenwick::new(8);
-        fw.add(0, 1);  // sum = 1
-        fw.add(1, 1);  // sum = 2 
-        fw.add(2, 3);  // sum = 5
-        fw.add(3, 1);  // sum = 6
-        fw.add(4, 1);  // sum = 7
+    #[should_panic(expected = "same size")]
+    fn difference_size_mismatch() {
+        let fw = Fenwick::<i32>::new(8);
+        fw.difference(&Fenwick::new(4));
+    }
+
+    #[test]
+    #[should_panic(expected = "same size")]
+    fn delta_since_size_mismatch() {
+        let fw = Fenwick::<i32>::new(8);
+        fw.delta_since(&Fenwick::new(16));
+    }
+
+    #[test]
+    #[should_panic(expected = "same size")]
+    fn zip_with_size_mismatch() {
+        let fw = Fenwick::<i32>::new(8);
+        fw.zip_with(&Fenwick::new(4), |a, b| a + b);
+    }
+
+    #[test]
+    fn rebuild_range_update() {
+        let mut fw = Fenwick::<i32>::new_mode(4, Mode::RangeUpdatePointQuery);
+        fw.range_add(0, 4, 2);
+        fw.range_add(1, 2, 3);
+        let elements = |fw: &Fenwick<i32>| {
+            (0..=fw.end()).map(|i| fw.point_get(i)).collect::<Vec<_>>()
+        };
+        assert_eq!(elements(&fw), [2, 5, 5, 2, 2]);
         
-        let mut iter = fw.iter();
-        assert_eq!(iter.next(), Some(1));
-        assert_eq!(iter.next(), Some(1));
-        assert_eq!(iter.next(), Some(3));
-        assert_eq!(iter.next(), Some(1));
-        assert_eq!(iter.next(), Some(1));
-        assert_eq!(iter.next(), Some(0));
-        assert_eq!(iter.next(), Some(0));
-        assert_eq!(iter.next(), Some(0));
-        assert_eq!(iter.next(), None);
+        let mut grown = fw.clone();
+        grown.resize(7, 9);
+        assert_eq!(grown.mode(), Mode::RangeUpdatePointQuery);
+        assert_eq!(elements(&grown), [2, 5, 5, 2, 2, 9, 9, 0, 0]);
+        grown.range_add(6, 8, 1);
+        assert_eq!(elements(&grown), [2, 5, 5, 2, 2, 9, 10, 1, 1]);
         
-        let mut iter = fw.into_iter();
-        assert_eq!(iter.next(), Some(1));
-        assert_eq!(iter.next(), Some(1));
-        assert_eq!(iter.next(), Some(3));
-        assert_eq!(iter.next(), Some(1));
-        assert_eq!(iter.next(), Some(1));
-        assert_eq!(iter.next(), Some(0));
-        assert_eq!(iter.next(), Some(0));
-        assert_eq!(iter.next(), Some(0));
-        assert_eq!(iter.next(), None);
+        let mut shrunk = fw.clone();
+        shrunk.resize(2, 0);
+        assert_eq!(shrunk.mode(), Mode::RangeUpdatePointQuery);
+        assert_eq!(elements(&shrunk), [2, 5, 0]);
+        
+        let mut padded = fw.clone();
+        padded.pad_front(3);
+        assert_eq!(padded.mode(), Mode::RangeUpdatePointQuery);
+        assert_eq!(elements(&padded), [0, 0, 0, 2, 5, 5, 2, 2, 0]);
+        
+        let both = fw.concat(&fw);
+        assert_eq!(both.mode(), Mode::RangeUpdatePointQuery);
+        assert_eq!(elements(&both)[..11], [2, 5, 5, 2, 2, 2, 5, 5, 2, 2, 0]);
+        
+        let mut other = Fenwick::new_mode(4, Mode::RangeUpdatePointQuery);
+        other.range_add(2, 3, 4);
+        let max = fw.zip_with(&other, |a, b| if a > b { a } else { b });
+        assert_eq!(max.mode(), Mode::RangeUpdatePointQuery);
+        assert_eq!(elements(&max), [2, 5, 5, 4, 2]);
+        
+        let hist = fw.histogram(2);
+        assert_eq!(hist.mode(), Mode::RangeUpdatePointQuery);
+        assert_eq!(elements(&hist), [7, 9, 0]);
     }
 
     #[test]
-    fn from_iterator() {
-        let fw = Fenwick::from_iter(vec![1, 1, 3, 1, 1, 0, 0, 0]);
-        assert_eq!(fw.prefix_sum(3), 6);
-        assert_eq!(fw.prefix_sum(4), 7);
-        assert_eq!(fw.prefix_sum(5), 7);
-        assert_eq!(fw.prefix_sum(6), 7);
-        assert_eq!(fw.prefix_sum(7), 7);
+    #[should_panic(expected = "same mode")]
+    fn concat_mode_mismatch() {
+        let fw = Fenwick::<i32>::new(4);
+        fw.concat(&Fenwick::new_mode(4, Mode::RangeUpdatePointQuery));
+    }
 
-        let fw = vec![1, 1, 3, 1, 1, 0, 0, 0, 0]
-                    .into_iter().collect::<Fenwick<i32>>();
-        assert_eq!(fw.prefix_sum(3), 6);
-        assert_eq!(fw.prefix_sum(4), 7);
-        assert_eq!(fw.prefix_sum(5), 7);
-        assert_eq!(fw.prefix_sum(6), 7);
-        assert_eq!(fw.prefix_sum(7), 7);
+    #[test]
+    fn extend_add_tree_range_update() {
+        let elements = |fw: &Fenwick<i32>| {
+            (0..=fw.end()).map(|i| fw.point_get(i)).collect::<Vec<_>>()
+        };
+        let mut fw = Fenwick::<i32>::new_mode(4, Mode::RangeUpdatePointQuery);
+        fw.range_add(0, 4, 2);
+        fw.range_add(1, 2, 3);
+        fw.extend_from_slice(&[7, 1]);
+        assert_eq!(fw.mode(), Mode::RangeUpdatePointQuery);
+        assert_eq!(elements(&fw), [2, 5, 5, 2, 2, 7, 1, 0, 0]);
+        fw.range_add(6, 8, 1);
+        assert_eq!(elements(&fw), [2, 5, 5, 2, 2, 7, 2, 1, 1]);
+        
+        let mut small = Fenwick::new_mode(4, Mode::RangeUpdatePointQuery);
+        small.range_add(0, 4, 1);
+        small.range_add(1, 2, 3);
+        let mut big = Fenwick::new_mode(8, Mode::RangeUpdatePointQuery);
+        big.add_tree_at(&small, 2);
+        assert_eq!(elements(&big), [0, 0, 1, 4, 4, 1, 1, 0, 0]);
+        big.add_tree_at(&small, 4);
+        assert_eq!(elements(&big), [0, 0, 1, 4, 5, 5, 5, 1, 1]);
+    }
 
-        let fw: Fenwick<_> = vec![1, 1, 3, 1, 1, 0, 0, 0, 0].into();
-        assert_eq!(fw.prefix_sum(3), 6);
-        assert_eq!(fw.prefix_sum(4), 7);
-        assert_eq!(fw.prefix_sum(5), 7);
-        assert_eq!(fw.prefix_sum(6), 7);
-        assert_eq!(fw.prefix_sum(7), 7);
+    #[test]
+    #[should_panic(expected = "same mode")]
+    fn add_tree_at_mode_mismatch() {
+        let mut fw = Fenwick::<i32>::new(8);
+        fw.add_tree_at(&Fenwick::new_mode(4, Mode::RangeUpdatePointQuery), 0);
+    }
 
+    #[test]
+    #[should_panic(expected = "length must be")]
+    fn mean_of_too_long() {
+        let fw = Fenwick::from_vec(vec![3, 3, 3, 3]);
+        fw.mean_of(6);
     }
 }
 
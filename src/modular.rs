@@ -0,0 +1,145 @@
+//! A Fenwick Tree whose sums are reduced modulo a constant.
+//!
+
+/// A prefix sum array over `u64` values where every sum is reduced modulo
+/// `M`, as used for hashing and combinatorics. All updates and queries take
+/// `O(log n)` time. Range sums subtract prefix sums by adding `M` before
+/// reducing, so the results stay in `0..M`.
+///
+/// Since the reduced prefix sums aren't monotonic, there are no rank
+/// queries: searching for the index where a prefix sum crosses a value has
+/// no meaning under modular arithmetic.
+///
+#[derive(Debug, Clone)]
+pub struct FenwickMod<const M: u64> {
+    data: Vec<u64>,
+}
+
+impl<const M: u64> FenwickMod<M> {
+    /// Creates a new tree holding `size` zero-valued elements. Panics if `M`
+    /// is 0.
+    ///
+    pub fn new(size: usize) -> Self {
+        assert!(M > 0, "The modulus must be non-zero.");
+        FenwickMod { data: vec![0; size + 1] }
+    }
+
+    /// Returns the number of elements in the tree.
+    ///
+    pub fn len(&self) -> usize {
+        self.data.len() - 1
+    }
+
+    /// Returns `true` if the tree has no elements.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Add `delta` (mod `M`) to element with index `idx` (zero-based).
+    ///
+    pub fn add(&mut self, idx: usize, delta: u64) {
+        debug_assert!(idx < self.len());
+        let delta = delta % M;
+        let mut i = idx + 1;
+        while i < self.data.len() {
+            self.data[i] = Self::add_mod(self.data[i], delta);
+            i += lsb!(i);
+        }
+    }
+
+    /// Set a single element's value (mod `M`).
+    ///
+    pub fn set(&mut self, idx: usize, value: u64) {
+        let cur_val = self.get(idx);
+        self.add(idx, Self::sub_mod(value % M, cur_val));
+    }
+
+    /// Return a single element's value, in `0..M`.
+    ///
+    pub fn get(&self, idx: usize) -> u64 {
+        self.range_sum(idx, idx)
+    }
+
+    /// Returns the sum mod `M` of the elements from index 0 to `idx`
+    /// inclusive.
+    ///
+    pub fn prefix_sum(&self, idx: usize) -> u64 {
+        debug_assert!(idx < self.len());
+        self.count_sum(idx + 1)
+    }
+
+    /// Returns the sum mod `M` of elements from `idx_i` to `idx_j` inclusive.
+    ///
+    pub fn range_sum(&self, idx_i: usize, idx_j: usize) -> u64 {
+        debug_assert!(idx_i <= idx_j && idx_j < self.len());
+        Self::sub_mod(self.count_sum(idx_j + 1), self.count_sum(idx_i))
+    }
+
+    /// Returns the sum mod `M` of all the elements.
+    ///
+    pub fn total(&self) -> u64 {
+        self.count_sum(self.len())
+    }
+
+    /// Returns the sum mod `M` of the first `count` elements.
+    ///
+    fn count_sum(&self, count: usize) -> u64 {
+        let mut sum = 0;
+        let mut i   = count;
+        while i != 0 {
+            sum = Self::add_mod(sum, self.data[i]);
+            i  -= lsb!(i);
+        }
+        sum
+    }
+
+    /// Adds two values already in `0..M`, without overflowing.
+    ///
+    fn add_mod(a: u64, b: u64) -> u64 {
+        ((a as u128 + b as u128) % M as u128) as u64
+    }
+
+    /// Subtracts two values already in `0..M`, adding `M` first so the
+    /// result isn't negative.
+    ///
+    fn sub_mod(a: u64, b: u64) -> u64 {
+        ((a as u128 + M as u128 - b as u128) % M as u128) as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn modular_sums() {
+        let mut fw = FenwickMod::<7>::new(6);
+        assert_eq!(fw.len(), 6);
+        
+        let values = [5, 6, 10, 3, 0, 13];
+        for (i, &v) in values.iter().enumerate() {
+            fw.add(i, v);
+        }
+        for i in 0..6 {
+            assert_eq!(fw.get(i), values[i] % 7);
+            assert_eq!(fw.prefix_sum(i), values[..=i].iter().sum::<u64>() % 7);
+        }
+        assert_eq!(fw.total(), 37 % 7);
+        
+        // prefix_sum(3) = 24 = 3 and prefix_sum(0) = 5: the range sum must
+        // wrap rather than go negative.
+        assert_eq!(fw.range_sum(1, 3), 19 % 7);
+        assert_eq!(fw.range_sum(2, 5), 26 % 7);
+        
+        fw.set(2, 4);
+        assert_eq!(fw.get(2), 4);
+        assert_eq!(fw.total(), 31 % 7);
+        
+        let mut fw = FenwickMod::<{ u64::MAX }>::new(2);
+        fw.add(0, u64::MAX - 1);
+        fw.add(1, 3);
+        assert_eq!(fw.total(), 2);
+        assert_eq!(fw.range_sum(1, 1), 3);
+    }
+}
@@ -0,0 +1,256 @@
+//! A multidimensional Fenwick Tree for prefix sums over boxes of a `D`
+//! dimensional grid.
+//!
+
+use std::ops::Add;
+use std::ops::Sub;
+use std::ops::AddAssign;
+use std::ops::SubAssign;
+
+use crate::FenwickError;
+
+/// Represents a `D` dimensional grid of values with `O(prod(log dim_i))`
+/// point updates and box sum queries. Box queries (`.range_sum()`) combine
+/// `2^D` prefix sums by inclusion-exclusion, giving them
+/// `O(2^D · prod(log dim_i))` time-complexity.
+///
+#[derive(Debug, Clone)]
+pub struct FenwickND<T, const D: usize> {
+    data    : Vec<T>,
+    dims    : [usize; D],
+    strides : [usize; D],
+}
+
+impl<T, const D: usize> FenwickND<T, D>
+where
+    T: Add<Output = T> + Sub<Output = T> + AddAssign + SubAssign + Default +
+       Copy,
+{
+    /// Creates a new zero-filled grid with the given extent along each
+    /// dimension. Coordinates along dimension `d` range from 0 to
+    /// `dims[d] - 1`.
+    ///
+    pub fn new(dims: [usize; D]) -> Self {
+        // Internally each dimension is 1-based, so reserve one extra slot.
+        let mut strides = [0; D];
+        let mut len     = 1;
+        for d in (0..D).rev() {
+            strides[d] = len;
+            len       *= dims[d] + 1;
+        }
+        FenwickND { data: vec![T::default(); len], dims, strides }
+    }
+
+    /// Returns the extent of the grid along each dimension.
+    ///
+    pub fn dims(&self) -> [usize; D] {
+        self.dims
+    }
+
+    /// Add `delta` to the element at `coords`.
+    ///
+    pub fn add(&mut self, coords: [usize; D], delta: T) {
+        debug_assert!((0..D).all(|d| coords[d] < self.dims[d]));
+        self.update(0, 0, &coords, delta);
+    }
+
+    /// Returns the sum of all elements in the box spanning from the origin to
+    /// `coords`, inclusive along each dimension.
+    ///
+    pub fn prefix_sum(&self, coords: [usize; D]) -> T {
+        debug_assert!((0..D).all(|d| coords[d] < self.dims[d]));
+        let mut counts = coords;
+        for c in counts.iter_mut() {
+            *c += 1;
+        }
+        self.query(0, 0, &counts)
+    }
+
+    /// Returns the sum of all elements in the box from `lo` to `hi`,
+    /// inclusive along each dimension. The result is assembled from `2^D`
+    /// prefix sums by inclusion-exclusion, so this method has
+    /// `O(2^D · prod(log dim_i))` time-complexity.
+    ///
+    pub fn range_sum(&self, lo: [usize; D], hi: [usize; D]) -> T {
+        debug_assert!((0..D).all(|d| lo[d] <= hi[d] && hi[d] < self.dims[d]));
+        // Terms with an odd number of lower corners are subtracted. They are
+        // summed separately so unsigned types never go negative mid-way.
+        let mut pos = T::default();
+        let mut neg = T::default();
+
+        for mask in 0..(1_usize << D) {
+            let mut counts = [0; D];
+            for d in 0..D {
+                counts[d] = if mask & (1 << d) != 0 { lo[d] }
+                            else                    { hi[d] + 1 };
+            }
+            if counts.contains(&0) {
+                continue;
+            }
+            if mask.count_ones() % 2 == 0 {
+                pos += self.query(0, 0, &counts);
+            } else {
+                neg += self.query(0, 0, &counts);
+            }
+        }
+        pos - neg
+    }
+
+    /// Walks the update path along dimension `dim`, recursing into the
+    /// remaining dimensions for each node visited.
+    ///
+    fn update(&mut self, dim: usize, offset: usize, coords: &[usize; D],
+              delta: T)
+    {
+        if dim == D {
+            self.data[offset] += delta;
+            return;
+        }
+        let mut i = coords[dim] + 1;
+        while i <= self.dims[dim] {
+            self.update(dim + 1, offset + i * self.strides[dim], coords, delta);
+            i += lsb!(i);
+        }
+    }
+
+    /// Sums the first `counts[d]` elements along each dimension `d` from
+    /// `dim` onward.
+    ///
+    fn query(&self, dim: usize, offset: usize, counts: &[usize; D]) -> T {
+        if dim == D {
+            return self.data[offset];
+        }
+        let mut sum = T::default();
+        let mut i   = counts[dim];
+        while i != 0 {
+            sum += self.query(dim + 1, offset + i * self.strides[dim], counts);
+            i   -= lsb!(i);
+        }
+        sum
+    }
+}
+
+/// A two dimensional tree, for prefix sums over rectangles of a matrix.
+///
+pub type Fenwick2D<T> = FenwickND<T, 2>;
+
+impl<T> FenwickND<T, 2>
+where
+    T: Add<Output = T> + Sub<Output = T> + AddAssign + SubAssign + Default +
+       Copy,
+{
+    /// Creates a two dimensional tree from a dense matrix, `data[row][col]`.
+    /// The values are placed in the grid and each axis is built in turn,
+    /// giving `O(rows · cols)` time-complexity. An error is returned if the
+    /// rows differ in length.
+    ///
+    pub fn from_matrix(data: Vec<Vec<T>>) -> Result<Self, FenwickError> {
+        let rows = data.len();
+        let cols = data.first().map_or(0, |row| row.len());
+        if let Some(row) = data.iter().position(|row| row.len() != cols) {
+            return Err(FenwickError::RaggedMatrix { row });
+        }
+        let mut fw = Self::new([rows, cols]);
+        let stride = fw.strides[0];
+        
+        for (r, row) in data.into_iter().enumerate() {
+            let offset = (r + 1) * stride + 1;
+            fw.data[offset..offset + cols].copy_from_slice(&row);
+        }
+        for r in 1..=rows {
+            for c in 1..=cols {
+                let j = c + lsb!(c);
+                if j <= cols {
+                    let d = fw.data[r * stride + c];
+                    fw.data[r * stride + j] += d;
+                }
+            }
+        }
+        for r in 1..=rows {
+            let j = r + lsb!(r);
+            if j <= rows {
+                for c in 1..=cols {
+                    let d = fw.data[r * stride + c];
+                    fw.data[j * stride + c] += d;
+                }
+            }
+        }
+        Ok(fw)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn range_sum_3d() {
+        let mut fw   = FenwickND::<i64, 3>::new([4, 3, 5]);
+        let mut grid = vec![0_i64; 4 * 3 * 5];
+        let cell     = |x: usize, y: usize, z: usize| (x * 3 + y) * 5 + z;
+
+        for x in 0..4 {
+            for y in 0..3 {
+                for z in 0..5 {
+                    let v = ((x * 7 + y * 3 + z * 5) % 11) as i64 - 3;
+                    grid[cell(x, y, z)] += v;
+                    fw.add([x, y, z], v);
+                }
+            }
+        }
+        fw.add([2, 1, 4], 9);
+        grid[cell(2, 1, 4)] += 9;
+
+        let brute = |lo: [usize; 3], hi: [usize; 3]| {
+            let mut sum = 0;
+            for x in lo[0]..=hi[0] {
+                for y in lo[1]..=hi[1] {
+                    for z in lo[2]..=hi[2] {
+                        sum += grid[cell(x, y, z)];
+                    }
+                }
+            }
+            sum
+        };
+        for lo_x in 0..4 {
+            for hi_x in lo_x..4 {
+                for lo_y in 0..3 {
+                    for hi_y in lo_y..3 {
+                        for lo_z in 0..5 {
+                            for hi_z in lo_z..5 {
+                                let lo = [lo_x, lo_y, lo_z];
+                                let hi = [hi_x, hi_y, hi_z];
+                                assert_eq!(fw.range_sum(lo, hi), brute(lo, hi));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        assert_eq!(fw.prefix_sum([3, 2, 4]), brute([0, 0, 0], [3, 2, 4]));
+    }
+
+    #[test]
+    fn from_matrix() {
+        let matrix = vec![vec![3, -1,  4],
+                          vec![1,  5, -9],
+                          vec![2,  6,  5]];
+        let fw = Fenwick2D::from_matrix(matrix.clone()).unwrap();
+        assert_eq!(fw.dims(), [3, 3]);
+        
+        for r0 in 0..3 {
+            for r1 in r0..3 {
+                for c0 in 0..3 {
+                    for c1 in c0..3 {
+                        let brute = (r0..=r1).flat_map(|r| &matrix[r][c0..=c1])
+                                             .sum::<i32>();
+                        assert_eq!(fw.range_sum([r0, c0], [r1, c1]), brute);
+                    }
+                }
+            }
+        }
+        let ragged = vec![vec![1, 2], vec![3, 4], vec![5]];
+        assert_eq!(Fenwick2D::from_matrix(ragged).unwrap_err(),
+                   FenwickError::RaggedMatrix { row: 2 });
+    }
+}
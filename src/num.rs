@@ -0,0 +1,172 @@
+//! Operations for primitive integer trees, enabled by the `num-traits`
+//! feature.
+//!
+
+use std::ops::AddAssign;
+use std::ops::SubAssign;
+
+use num_traits::PrimInt;
+
+use crate::{padded_size, Fenwick, FenwickError, Mode};
+
+impl<T> Fenwick<T>
+where
+    T: PrimInt + AddAssign + SubAssign + Default,
+{
+    /// Add one to element with index `idx`, e.g. to count an occurrence.
+    /// Requires the `num-traits` feature.
+    ///
+    pub fn increment(&mut self, idx: usize) {
+        self.add(idx, T::one());
+    }
+
+    /// Subtract one from element with index `idx`. Requires the `num-traits`
+    /// feature.
+    ///
+    pub fn decrement(&mut self, idx: usize) {
+        self.sub(idx, T::one());
+    }
+
+    /// Add `delta` to element with index `idx`, checking every node updated
+    /// for overflow. If the index is out of range or any node would
+    /// overflow, an error is returned and the tree is left unchanged.
+    /// Requires the `num-traits` feature.
+    ///
+    pub fn checked_add(&mut self, idx: usize, delta: T) 
+        -> Result<(), FenwickError> 
+    {
+        self.checked_update(idx, |node| node.checked_add(&delta))
+    }
+
+    /// Subtract `delta` from element with index `idx`, checking every node
+    /// updated for overflow. If the index is out of range or any node would
+    /// overflow, an error is returned and the tree is left unchanged.
+    /// Requires the `num-traits` feature.
+    ///
+    pub fn checked_sub(&mut self, idx: usize, delta: T) 
+        -> Result<(), FenwickError> 
+    {
+        self.checked_update(idx, |node| node.checked_sub(&delta))
+    }
+
+    /// Creates a new tree from the element values in `data` as `.from_vec()`
+    /// does, checking for overflow. If any prefix sum or any node of the
+    /// tree can't be represented in `T`, the error reports the first index
+    /// where that happens, so bad input can be traced to the element that
+    /// tipped it over. Nodes are computed as differences of the prefix
+    /// sums, so signed values that cancel out don't cause false alarms. The
+    /// size is adjusted as in `.new()`. This method has `O(n)`
+    /// time-complexity and requires the `num-traits` feature.
+    ///
+    pub fn from_deltas_checked(data: Vec<T>) -> Result<Self, FenwickError> {
+        let size     = padded_size(data.len());
+        let mut data = data;
+        data.resize(size, T::default());
+        
+        let mut sums = Vec::with_capacity(size);
+        sums.push(data[0]);
+        for j in 1..size {
+            let sum = sums[j - 1].checked_add(&data[j])
+                                 .ok_or(FenwickError::Overflow { idx: j })?;
+            sums.push(sum);
+            data[j] = sum.checked_sub(&sums[j - lsb!(j)])
+                         .ok_or(FenwickError::Overflow { idx: j })?;
+        }
+        Ok(Fenwick { data, size, mode: Mode::PointUpdateRangeQuery })
+    }
+
+    /// Applies `op` to each node on the update path of `idx`. All the nodes
+    /// are checked before any is written, so a failure leaves no partial
+    /// update behind.
+    ///
+    fn checked_update<F>(&mut self, idx: usize, op: F) 
+        -> Result<(), FenwickError>
+    where
+        F: Fn(T) -> Option<T>,
+    {
+        if idx >= self.size {
+            return Err(FenwickError::IndexOutOfBounds { idx, end: self.end() });
+        }
+        let mut path = vec![idx];
+        if idx > 0 {
+            let mut i = idx + lsb!(idx);
+            while i < self.size {
+                path.push(i);
+                i += lsb!(i);
+            }
+        }
+        let updated = path.iter()
+                          .map(|&i| op(self.data[i]))
+                          .collect::<Option<Vec<T>>>()
+                          .ok_or(FenwickError::Overflow { idx })?;
+
+        for (i, node) in path.into_iter().zip(updated) {
+            self.data[i] = node;
+        }
+        assert_invariants!(self);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn increment_decrement() {
+        let mut fw = Fenwick::<u32>::new(8);
+        fw.increment(0);
+        fw.increment(3);
+        fw.increment(3);
+        fw.decrement(3);
+        assert_eq!(fw.get(0), 1);
+        assert_eq!(fw.get(3), 1);
+        assert_eq!(fw.total(), 2);
+    }
+
+    #[test]
+    fn checked_add_sub() {
+        let mut fw = Fenwick::<u8>::from_vec(vec![100, 50, 50, 0]);
+        fw.add(4, 10);
+        assert_eq!(fw.checked_add(3, 5), Ok(()));
+        assert_eq!(fw.get(3), 5);
+
+        // Node 4 covers indices 1 to 4, which would sum past 255.
+        let before = fw.data.clone();
+        assert_eq!(fw.checked_add(2, 160), 
+                   Err(FenwickError::Overflow { idx: 2 }));
+        assert_eq!(fw.data, before);
+
+        assert_eq!(fw.checked_add(0, 200), 
+                   Err(FenwickError::Overflow { idx: 0 }));
+        assert_eq!(fw.checked_sub(1, 51), 
+                   Err(FenwickError::Overflow { idx: 1 }));
+        assert_eq!(fw.checked_sub(1, 50), Ok(()));
+        assert_eq!(fw.get(1), 0);
+        assert_eq!(fw.checked_add(5, 1), 
+                   Err(FenwickError::IndexOutOfBounds { idx: 5, end: 4 }));
+        assert_eq!(fw.decumulate(), [100, 0, 50, 5, 10]);
+    }
+
+    #[test]
+    fn from_deltas_checked() {
+        let values = vec![100_u8, 50, 0, 100, 10, 0];
+        let fw     = Fenwick::from_deltas_checked(values[..4].to_vec());
+        assert_eq!(fw, Ok(Fenwick::from_vec(values[..4].to_vec())));
+        
+        // The prefix sums run 100, 150, 150, 250, then 260 at index 4.
+        assert_eq!(Fenwick::from_deltas_checked(values),
+                   Err(FenwickError::Overflow { idx: 4 }));
+        
+        // Every prefix sum fits in an i8, but node 4 covers indices 1 to 4,
+        // which sum to 200.
+        let values = vec![-100_i8, 50, 50, 50, 50];
+        assert_eq!(Fenwick::from_deltas_checked(values),
+                   Err(FenwickError::Overflow { idx: 4 }));
+        
+        let values = vec![-100_i16, 100, 100, -100, 127];
+        let fw     = Fenwick::from_deltas_checked(values.clone()).unwrap();
+        assert_eq!(fw.decumulate()[..5], values[..]);
+        assert_eq!(fw, Fenwick::from_vec(values));
+    }
+}
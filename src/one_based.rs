@@ -0,0 +1,148 @@
+//! A 1-based indexing wrapper around the 0-based Fenwick Tree.
+//!
+
+use std::ops::Add;
+use std::ops::Sub;
+use std::ops::AddAssign;
+use std::ops::SubAssign;
+use std::cmp::PartialOrd;
+
+use crate::Fenwick;
+
+/// A `Fenwick` tree addressed with 1-based indices, as in much of the Fenwick
+/// Tree literature. Index `i` here refers to index `i - 1` of the underlying
+/// 0-based tree, so valid indices run from 1 to `.end()` inclusive. Indices
+/// returned by the queries are translated the same way.
+///
+#[derive(Debug, Clone)]
+pub struct FenwickOneBased<T> {
+    fw: Fenwick<T>,
+}
+
+impl<T> FenwickOneBased<T>
+where
+    T: Add<Output = T> + Sub<Output = T> + AddAssign + SubAssign +
+       PartialOrd + Default + Copy,
+{
+    /// Creates a new zero-filled tree with indices from 1 to at least
+    /// `size`. The size is adjusted as in `Fenwick::new()`.
+    ///
+    pub fn new(size: usize) -> Self {
+        FenwickOneBased { fw: Fenwick::new(size) }
+    }
+
+    /// Returns the underlying 0-based tree.
+    ///
+    pub fn into_inner(self) -> Fenwick<T> {
+        self.fw
+    }
+
+    /// Returns the index of the last element.
+    ///
+    pub fn end(&self) -> usize {
+        self.fw.end() + 1
+    }
+
+    /// Returns the sum of the elements from index 1 to `idx` inclusive.
+    ///
+    pub fn prefix_sum(&self, idx: usize) -> T {
+        self.fw.prefix_sum(Self::zero_based(idx))
+    }
+
+    /// Returns the total prefix sum of all the elements.
+    ///
+    pub fn total(&self) -> T {
+        self.fw.total()
+    }
+
+    /// Add `delta` to element with index `idx` (one-based).
+    ///
+    pub fn add(&mut self, idx: usize, delta: T) {
+        self.fw.add(Self::zero_based(idx), delta);
+    }
+
+    /// Subtract `delta` from element with index `idx`.
+    ///
+    pub fn sub(&mut self, idx: usize, delta: T) {
+        self.fw.sub(Self::zero_based(idx), delta);
+    }
+
+    /// Set a single element's value.
+    ///
+    pub fn set(&mut self, idx: usize, value: T) {
+        self.fw.set(Self::zero_based(idx), value);
+    }
+
+    /// Return a single element's value.
+    ///
+    pub fn get(&self, idx: usize) -> T {
+        self.fw.get(Self::zero_based(idx))
+    }
+
+    /// Returns the sum of elements from `idx_i` to `idx_j` inclusive.
+    ///
+    pub fn range_sum(&self, idx_i: usize, idx_j: usize) -> T {
+        self.fw.range_sum(Self::zero_based(idx_i), Self::zero_based(idx_j))
+    }
+
+    /// Find the largest index with `.prefix_sum(index) <= value`.
+    /// NOTE: Requires all values are non-negative.
+    ///
+    pub fn rank_query(&self, value: T) -> usize {
+        self.fw.rank_query(value) + 1
+    }
+
+    /// Find the smallest index with `.prefix_sum(index) >= value`.
+    /// NOTE: Requires all values are non-negative.
+    ///
+    pub fn min_rank_query(&self, value: T) -> usize {
+        self.fw.min_rank_query(value) + 1
+    }
+
+    /// Translates a 1-based index into a 0-based one.
+    ///
+    fn zero_based(idx: usize) -> usize {
+        debug_assert!(idx >= 1, "Indices start at 1.");
+        idx.wrapping_sub(1)
+    }
+}
+
+impl<T> From<Fenwick<T>> for FenwickOneBased<T> {
+    fn from(fw: Fenwick<T>) -> Self {
+        FenwickOneBased { fw }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn one_based() {
+        let mut fw0 = Fenwick::new(8);
+        let mut fw1 = FenwickOneBased::new(8);
+        assert_eq!(fw1.end(), fw0.end() + 1);
+        
+        for (i, &v) in [1, 1, 3, 1, 1, 0, 0, 2, 4].iter().enumerate() {
+            fw0.add(i, v);
+            fw1.add(i + 1, v);
+        }
+        fw0.sub(2, 1);
+        fw1.sub(3, 1);
+        fw0.set(5, 6);
+        fw1.set(6, 6);
+        
+        for i in 0..=fw0.end() {
+            assert_eq!(fw1.get(i + 1), fw0.get(i));
+            assert_eq!(fw1.prefix_sum(i + 1), fw0.prefix_sum(i));
+        }
+        assert_eq!(fw1.range_sum(2, 4), fw0.range_sum(1, 3));
+        assert_eq!(fw1.total(), fw0.total());
+        assert_eq!(fw1.rank_query(6), fw0.rank_query(6) + 1);
+        assert_eq!(fw1.min_rank_query(7), fw0.min_rank_query(7) + 1);
+        
+        let fw1 = FenwickOneBased::from(fw0.clone());
+        assert_eq!(fw1.prefix_sum(3), fw0.prefix_sum(2));
+        assert_eq!(fw1.into_inner().data, fw0.data);
+    }
+}
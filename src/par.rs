@@ -0,0 +1,138 @@
+//! Parallel operations on Fenwick Trees, enabled by the `rayon` feature.
+//!
+
+use std::ops::Add;
+use std::ops::Sub;
+use std::ops::AddAssign;
+use std::ops::SubAssign;
+use std::cmp::PartialOrd;
+
+use rayon::prelude::*;
+
+use crate::{build_add, padded_size, Fenwick, Mode};
+
+/// The fewest elements worth handing to a separate task.
+///
+const MIN_CHUNK: usize = 4096;
+
+/// The fewest elements a tree must have before `.from_vec_par()` builds it
+/// in parallel.
+///
+const PAR_BUILD_THRESHOLD: usize = 1 << 16;
+
+impl<T> Fenwick<T>
+where
+    T: Add<Output = T> + Sub<Output = T> + AddAssign + SubAssign +
+       PartialOrd + Default + Copy + Send + Sync,
+{
+    /// Returns the prefix sum at every index as a vector, computed in
+    /// parallel. The indices are split into chunks; each chunk seeds its
+    /// running sum with a single `O(log n)` prefix sum query, then recovers
+    /// its element values from the nodes directly, so the chunks don't depend
+    /// on each other. The output is identical to `.prefix_sums_vec()`.
+    /// Requires the `rayon` feature.
+    ///
+    pub fn prefix_sums_par(&self) -> Vec<T> {
+        let mut sums  = vec![T::default(); self.size];
+        let n_threads = rayon::current_num_threads().max(1);
+        let chunk     = (self.size / (n_threads * 4)).max(MIN_CHUNK);
+        
+        sums.par_chunks_mut(chunk).enumerate().for_each(|(c, out)| {
+            let start   = c * chunk;
+            let mut sum = if start == 0 { T::default() } 
+                          else          { self.prefix_sum(start - 1) };
+            for (k, s) in out.iter_mut().enumerate() {
+                sum += self.node_value(start + k);
+                *s   = sum;
+            }
+        });
+        sums
+    }
+
+    /// Creates a new Fenwick instance from the provided vector, building the
+    /// nodes in parallel. Inputs with fewer than 2^16 elements aren't worth
+    /// splitting up and are built sequentially, as by `Fenwick::from()`.
+    /// Both builds produce identical trees. Requires the `rayon` feature.
+    ///
+    pub fn from_vec_par(vec: Vec<T>) -> Self {
+        if vec.len() < PAR_BUILD_THRESHOLD {
+            return Self::from_vec(vec);
+        }
+        let size     = padded_size(vec.len());
+        let mut data = vec;
+        
+        data.resize(size, T::default());
+        Self::build_slice_par(&mut data);
+        Fenwick { data, size, mode: Mode::PointUpdateRangeQuery }
+    }
+
+    /// Converts the element values in `data` into tree nodes in place, in 
+    /// parallel. Each node only depends on nodes at lower levels (those with
+    /// a smaller `lsb`), so the levels are processed in order, and the nodes
+    /// within a level in parallel. Splitting the nodes into aligned blocks of
+    /// twice the level's width puts each node in the same block as all of its
+    /// children. The work is `O(n)` overall, as with the sequential build.
+    ///
+    fn build_slice_par(data: &mut [T]) {
+        if data.len() < 2 {
+            return;
+        }
+        // Node i is nodes[i - 1].
+        let nodes = &mut data[1..];
+        let len   = nodes.len();
+        let mut width = 2;
+        
+        while width <= len {
+            let block = width * 2;
+            nodes.par_chunks_mut(block)
+                 .with_min_len((MIN_CHUNK / block).max(1))
+                 .enumerate()
+                 .for_each(|(c, chunk)| {
+                     if chunk.len() >= width {
+                         let node     = c * block + width;
+                         let mut sum  = chunk[width - 1];
+                         let mut step = 1;
+                         while step < width {
+                             let child = chunk[width - 1 - step];
+                             sum       = build_add(sum, child, node);
+                             step    <<= 1;
+                         }
+                         chunk[width - 1] = sum;
+                     }
+                 });
+            width <<= 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn prefix_sums_par() {
+        let values = (0..100_000_i64).map(|n| (n * 7919) % 101 - 50)
+                                     .collect::<Vec<_>>();
+        let fw = Fenwick::from_vec(values);
+        assert_eq!(fw.prefix_sums_par(), fw.prefix_sums_vec());
+        
+        let fw = Fenwick::from_vec(vec![1, 1, 3, 1]);
+        assert_eq!(fw.prefix_sums_par(), vec![1, 2, 5, 6, 6]);
+    }
+
+    #[test]
+    fn build_par() {
+        for &len in &[0, 1, 2, 3, 5, 8, 9, 100, 1025, 70_000] {
+            let values = (0..len as i64).map(|n| (n * 7919) % 101 - 50)
+                                        .collect::<Vec<_>>();
+            let mut seq = values.clone();
+            let mut par = values.clone();
+            Fenwick::build_slice(&mut seq);
+            Fenwick::build_slice_par(&mut par);
+            assert_eq!(seq, par);
+            
+            let fw = Fenwick::from_vec_par(values.clone());
+            assert_eq!(fw.data, Fenwick::from_vec(values).data);
+        }
+    }
+}
@@ -0,0 +1,154 @@
+//! A Fenwick Tree over products instead of sums.
+//!
+
+use std::ops::{Div, Mul};
+
+/// A prefix product array. Multiplying an element by a factor and querying
+/// the product of a prefix both take `O(log n)` time. Range products divide
+/// one prefix product by another, so every factor must be invertible: a
+/// zero factor can't be divided back out, and will corrupt the range
+/// products and element values that depend on it. Integer types also lose
+/// the remainders of their divisions, so the tree is best suited to floats
+/// or rationals.
+///
+#[derive(Debug, Clone)]
+pub struct FenwickProduct<T> {
+    data : Vec<T>,
+    one  : T,
+}
+
+impl<T> FenwickProduct<T>
+where
+    T: Mul<Output = T> + Div<Output = T> + From<u8> + PartialEq + Copy,
+{
+    /// Creates a new tree holding `size` elements, each set to 1.
+    ///
+    pub fn new(size: usize) -> Self {
+        let one = T::from(1);
+        FenwickProduct { data: vec![one; size + 1], one }
+    }
+
+    /// Creates a new tree holding the given values. Panics if any of them
+    /// is zero.
+    ///
+    pub fn from_slice(values: &[T]) -> Self {
+        let mut fw = Self::new(values.len());
+        for (i, &v) in values.iter().enumerate() {
+            fw.multiply(i, v);
+        }
+        fw
+    }
+
+    /// Returns the number of elements in the tree.
+    ///
+    pub fn len(&self) -> usize {
+        self.data.len() - 1
+    }
+
+    /// Returns `true` if the tree has no elements.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Multiplies the element at `idx` (zero-based) by `factor`. Panics if
+    /// `factor` is zero.
+    ///
+    pub fn multiply(&mut self, idx: usize, factor: T) {
+        assert!(factor != T::from(0), "Factors must be non-zero.");
+        debug_assert!(idx < self.len());
+        let mut i = idx + 1;
+        while i < self.data.len() {
+            self.data[i] = self.data[i] * factor;
+            i += lsb!(i);
+        }
+    }
+
+    /// Returns the product of the elements from index 0 to `idx` inclusive.
+    ///
+    pub fn prefix_product(&self, idx: usize) -> T {
+        debug_assert!(idx < self.len());
+        self.count_product(idx + 1)
+    }
+
+    /// Returns the product of the elements from `idx_i` to `idx_j`
+    /// inclusive.
+    ///
+    pub fn range_product(&self, idx_i: usize, idx_j: usize) -> T {
+        debug_assert!(idx_i <= idx_j && idx_j < self.len());
+        self.count_product(idx_j + 1) / self.count_product(idx_i)
+    }
+
+    /// Return a single element's value.
+    ///
+    pub fn get(&self, idx: usize) -> T {
+        self.range_product(idx, idx)
+    }
+
+    /// Set a single element's value. Panics if `value` is zero.
+    ///
+    pub fn set(&mut self, idx: usize, value: T) {
+        let cur_val = self.get(idx);
+        self.multiply(idx, value / cur_val);
+    }
+
+    /// Returns the product of all the elements.
+    ///
+    pub fn total(&self) -> T {
+        self.count_product(self.len())
+    }
+
+    /// Returns the product of the first `count` elements.
+    ///
+    fn count_product(&self, count: usize) -> T {
+        let mut prod = self.one;
+        let mut i    = count;
+        while i != 0 {
+            prod = prod * self.data[i];
+            i   -= lsb!(i);
+        }
+        prod
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn prefix_products() {
+        // Powers of two keep the float products exact.
+        let values = [2.0, 0.5, 4.0, 8.0, 0.25, 1.0];
+        let mut fw = FenwickProduct::<f64>::from_slice(&values);
+        assert_eq!(fw.len(), 6);
+
+        for i in 0..6 {
+            assert_eq!(fw.get(i), values[i]);
+            assert_eq!(fw.prefix_product(i),
+                       values[..=i].iter().product::<f64>());
+        }
+        assert_eq!(fw.total(), 8.0);
+        assert_eq!(fw.range_product(1, 3), 16.0);
+        assert_eq!(fw.range_product(3, 4), 2.0);
+
+        fw.multiply(2, 0.5);
+        assert_eq!(fw.get(2), 2.0);
+        assert_eq!(fw.total(), 4.0);
+
+        fw.set(0, 4.0);
+        assert_eq!(fw.get(0), 4.0);
+        assert_eq!(fw.prefix_product(1), 2.0);
+        assert_eq!(fw.total(), 8.0);
+
+        let fw = FenwickProduct::<f64>::new(3);
+        assert_eq!(fw.total(), 1.0);
+        assert!(FenwickProduct::<f32>::new(0).is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_factor() {
+        let mut fw = FenwickProduct::<f64>::new(4);
+        fw.multiply(1, 0.0);
+    }
+}
@@ -0,0 +1,214 @@
+//! A frequency tree over a range of signed values, answering rank queries.
+//!
+
+use std::convert::TryFrom;
+
+use crate::Fenwick;
+
+/// Counts occurrences of signed values from the range `min..=max` and
+/// answers order statistics over them, such as the number of values at or
+/// below a threshold or the `k`th smallest value.
+///
+/// Rank queries on a `Fenwick` need non-negative elements, so signed values
+/// can't be stored in a tree directly. Instead, each value `v` is shifted by
+/// the offset `min` and counted at index `v - min` of a `Fenwick<u64>`: the
+/// counts are never negative, and the indices preserve the order of the
+/// values. Rank queries run on the counts and their results are shifted
+/// back by the offset. All operations take `O(log n)` time-complexity,
+/// where `n` is the width of the range.
+///
+#[derive(Debug, Clone)]
+pub struct SignedRankFenwick {
+    offset : i64,
+    width  : usize,
+    fw     : Fenwick<u64>,
+}
+
+impl SignedRankFenwick {
+    /// Creates an empty tree for values from `min` to `max` inclusive. Panics
+    /// if `min` is greater than `max`, or if the range holds more values than
+    /// a `usize` can count, as the full `i64` range does.
+    ///
+    pub fn new(min: i64, max: i64) -> Self {
+        assert!(min <= max, "The minimum must not exceed the maximum.");
+        let width = usize::try_from(max as i128 - min as i128 + 1)
+                          .expect("The range is too wide to count its values.");
+        SignedRankFenwick { offset: min, width, fw: Fenwick::new(width) }
+    }
+
+    /// Returns the smallest value the tree can count.
+    ///
+    pub fn min(&self) -> i64 {
+        self.offset
+    }
+
+    /// Returns the largest value the tree can count.
+    ///
+    pub fn max(&self) -> i64 {
+        self.to_value(self.width - 1)
+    }
+
+    /// Counts one more occurrence of `value`. Panics if `value` is outside
+    /// the tree's range.
+    ///
+    pub fn insert(&mut self, value: i64) {
+        let idx = self.to_index(value);
+        self.fw.add(idx, 1);
+    }
+
+    /// Removes one occurrence of `value`. Panics if `value` is outside the
+    /// tree's range.
+    /// NOTE: `value` must have been inserted.
+    ///
+    pub fn remove(&mut self, value: i64) {
+        let idx = self.to_index(value);
+        debug_assert!(self.fw.get(idx) > 0, "The value isn't in the tree.");
+        self.fw.sub(idx, 1);
+    }
+
+    /// Returns the number of occurrences of `value`.
+    ///
+    pub fn count(&self, value: i64) -> u64 {
+        if value < self.min() || value > self.max() {
+            0
+        } else {
+            self.fw.get(self.to_index(value))
+        }
+    }
+
+    /// Returns the number of occurrences of values `<= value`. `value` may
+    /// be outside the tree's range.
+    ///
+    pub fn rank(&self, value: i64) -> u64 {
+        if value < self.min() {
+            0
+        } else if value >= self.max() {
+            self.fw.total()
+        } else {
+            self.fw.prefix_sum(self.to_index(value))
+        }
+    }
+
+    /// Find the largest value with `.rank(value) <= count`. Returns `None`
+    /// if even the occurrences of the minimum value exceed `count`.
+    ///
+    pub fn rank_query(&self, count: u64) -> Option<i64> {
+        if self.fw.prefix_sum(0) > count {
+            None
+        } else {
+            // The tree's padding holds no counts; don't report it.
+            let idx = self.fw.rank_query(count).min(self.width - 1);
+            Some(self.to_value(idx))
+        }
+    }
+
+    /// Find the smallest value with `.rank(value) >= count`, which for
+    /// `count > 0` is the `count`th smallest value counted. Returns `None` if
+    /// fewer than `count` occurrences are in the tree.
+    ///
+    pub fn min_rank_query(&self, count: u64) -> Option<i64> {
+        if count > self.fw.total() {
+            None
+        } else {
+            Some(self.to_value(self.fw.min_rank_query(count)))
+        }
+    }
+
+    /// Returns the number of occurrences in total.
+    ///
+    pub fn len(&self) -> usize {
+        self.fw.total() as usize
+    }
+
+    /// Returns whether no occurrences are counted.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Shifts `value` by the offset to its index in the tree.
+    ///
+    fn to_index(&self, value: i64) -> usize {
+        assert!(value >= self.min() && value <= self.max(),
+                "The value is outside the tree's range.");
+        (value as i128 - self.offset as i128) as usize
+    }
+
+    /// Shifts the index `idx` back to the value counted there.
+    ///
+    fn to_value(&self, idx: usize) -> i64 {
+        (self.offset as i128 + idx as i128) as i64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn signed_ranks() {
+        let mut sr = SignedRankFenwick::new(-10, 10);
+        assert_eq!((sr.min(), sr.max()), (-10, 10));
+        assert!(sr.is_empty());
+        assert_eq!(sr.min_rank_query(1), None);
+
+        for value in [-7, 3, -7, 0, 10, -10, 3, -1] {
+            sr.insert(value);
+        }
+        // Sorted: -10, -7, -7, -1, 0, 3, 3, 10
+        assert_eq!(sr.len(), 8);
+        assert_eq!(sr.count(-7), 2);
+        assert_eq!(sr.count(-20), 0);
+        assert_eq!(sr.rank(-11), 0);
+        assert_eq!(sr.rank(-7), 3);
+        assert_eq!(sr.rank(-2), 3);
+        assert_eq!(sr.rank(2), 5);
+        assert_eq!(sr.rank(99), 8);
+
+        let sorted = [-10, -7, -7, -1, 0, 3, 3, 10];
+        for (k, &value) in sorted.iter().enumerate() {
+            assert_eq!(sr.min_rank_query(k as u64 + 1), Some(value));
+        }
+        assert_eq!(sr.min_rank_query(9), None);
+
+        assert_eq!(sr.rank_query(0), None);
+        assert_eq!(sr.rank_query(1), Some(-8));
+        assert_eq!(sr.rank_query(3), Some(-2));
+        assert_eq!(sr.rank_query(7), Some(9));
+        assert_eq!(sr.rank_query(8), Some(10));
+
+        sr.remove(-10);
+        sr.remove(3);
+        assert_eq!(sr.rank_query(0), Some(-8));
+        assert_eq!(sr.min_rank_query(1), Some(-7));
+        assert_eq!(sr.min_rank_query(5), Some(3));
+        assert_eq!(sr.rank(3), 5);
+    }
+
+    #[test]
+    fn full_range() {
+        let mut sr = SignedRankFenwick::new(-3, 3);
+        sr.insert(-3);
+        sr.insert(3);
+        assert_eq!(sr.min_rank_query(1), Some(-3));
+        assert_eq!(sr.min_rank_query(2), Some(3));
+        assert_eq!(sr.rank_query(1), Some(2));
+
+        let sr = SignedRankFenwick::new(i64::MIN, i64::MIN + 4);
+        assert_eq!(sr.max(), i64::MIN + 4);
+        assert_eq!(sr.rank_query(0), Some(i64::MIN + 4));
+    }
+
+    #[test]
+    #[should_panic(expected = "too wide")]
+    fn full_i64_range() {
+        SignedRankFenwick::new(i64::MIN, i64::MAX);
+    }
+
+    #[test]
+    #[should_panic(expected = "outside the tree's range")]
+    fn out_of_range() {
+        let mut sr = SignedRankFenwick::new(-3, 3);
+        sr.insert(4);
+    }
+}
@@ -0,0 +1,169 @@
+//! A Fenwick Tree that also counts its non-zero elements.
+//!
+
+use std::ops::Add;
+use std::ops::Sub;
+use std::ops::AddAssign;
+use std::ops::SubAssign;
+use std::cmp::PartialOrd;
+
+use crate::Fenwick;
+
+/// A `Fenwick` tree paired with a second tree counting which elements are
+/// non-zero, for sparse data where the number of occupied indices in a range
+/// matters as much as their sum. A sum tree alone can't answer this, since
+/// positive and negative values may cancel out or a single large value may
+/// stand in for many small ones.
+///
+/// Every update reads the element's old value to see whether it crosses
+/// zero, and adjusts the count tree to match, so updates and queries all
+/// take `O(log n)` time-complexity. The count tree costs one extra `usize`
+/// per element.
+///
+#[derive(Debug, Clone)]
+pub struct SparseFenwick<T> {
+    fw      : Fenwick<T>,
+    nonzero : Fenwick<usize>,
+}
+
+impl<T> SparseFenwick<T>
+where
+    T: Add<Output = T> + Sub<Output = T> + AddAssign + SubAssign +
+       PartialOrd + Default + Copy,
+{
+    /// Creates a new zero-filled tree. The size is adjusted as in
+    /// `Fenwick::new()`.
+    ///
+    pub fn new(size: usize) -> Self {
+        Self::from(Fenwick::new(size))
+    }
+
+    /// Returns the underlying sum tree, dropping the counts.
+    ///
+    pub fn into_inner(self) -> Fenwick<T> {
+        self.fw
+    }
+
+    /// Returns the index of the last element.
+    ///
+    pub fn end(&self) -> usize {
+        self.fw.end()
+    }
+
+    /// Returns the sum of the elements from index 0 to `idx` inclusive.
+    ///
+    pub fn prefix_sum(&self, idx: usize) -> T {
+        self.fw.prefix_sum(idx)
+    }
+
+    /// Returns the sum of elements from `idx_i` to `idx_j` inclusive.
+    ///
+    pub fn range_sum(&self, idx_i: usize, idx_j: usize) -> T {
+        self.fw.range_sum(idx_i, idx_j)
+    }
+
+    /// Returns the number of elements from `start` to `end` inclusive whose
+    /// values are non-zero.
+    ///
+    pub fn range_count_nonzero(&self, start: usize, end: usize) -> usize {
+        self.nonzero.range_sum(start, end)
+    }
+
+    /// Returns the number of non-zero elements in the whole tree.
+    ///
+    pub fn count_nonzero(&self) -> usize {
+        self.nonzero.total()
+    }
+
+    /// Return a single element's value.
+    ///
+    pub fn get(&self, idx: usize) -> T {
+        self.fw.get(idx)
+    }
+
+    /// Add `delta` to element with index `idx`.
+    ///
+    pub fn add(&mut self, idx: usize, delta: T) {
+        let old = self.fw.get(idx);
+        self.fw.add(idx, delta);
+        self.recount(idx, old, old + delta);
+    }
+
+    /// Subtract `delta` from element with index `idx`.
+    ///
+    pub fn sub(&mut self, idx: usize, delta: T) {
+        let old = self.fw.get(idx);
+        self.fw.sub(idx, delta);
+        self.recount(idx, old, old - delta);
+    }
+
+    /// Set a single element's value.
+    ///
+    pub fn set(&mut self, idx: usize, value: T) {
+        let old = self.fw.get(idx);
+        self.fw.set(idx, value);
+        self.recount(idx, old, value);
+    }
+
+    /// Updates the count of non-zero elements for an element whose value
+    /// changed from `old` to `new`.
+    ///
+    fn recount(&mut self, idx: usize, old: T, new: T) {
+        let zero = T::default();
+        match (old != zero, new != zero) {
+            (false, true) => self.nonzero.add(idx, 1),
+            (true, false) => self.nonzero.sub(idx, 1),
+            _             => (),
+        }
+    }
+}
+
+impl<T> From<Fenwick<T>> for SparseFenwick<T>
+where
+    T: Add<Output = T> + Sub<Output = T> + AddAssign + SubAssign +
+       PartialOrd + Default + Copy,
+{
+    fn from(fw: Fenwick<T>) -> Self {
+        let zero    = T::default();
+        let flags   = fw.values().map(|v| usize::from(v != zero)).collect();
+        let nonzero = Fenwick::from_vec_unpadded(flags);
+        SparseFenwick { fw, nonzero }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn range_count_nonzero() {
+        let fw     = Fenwick::from_vec(vec![3, 0, -3, 0, 5, 0, 0, 2, 0]);
+        let mut fw = SparseFenwick::from(fw);
+        assert_eq!(fw.nonzero.end(), fw.end());
+        assert_eq!(fw.count_nonzero(), 4);
+        assert_eq!(fw.range_count_nonzero(0, 2), 2);
+        assert_eq!(fw.range_sum(0, 2), 0);
+        assert_eq!(fw.range_count_nonzero(5, 6), 0);
+
+        // Zero to non-zero, non-zero to non-zero, and non-zero to zero.
+        fw.add(5, 4);
+        fw.add(4, 1);
+        fw.sub(2, -3);
+        assert_eq!(fw.count_nonzero(), 4);
+        assert_eq!(fw.range_count_nonzero(2, 5), 2);
+        assert_eq!(fw.range_sum(2, 5), 10);
+
+        fw.set(0, 0);
+        fw.set(1, 7);
+        fw.set(7, 0);
+        fw.add(8, 0);
+        assert_eq!(fw.count_nonzero(), 3);
+        assert_eq!(fw.range_count_nonzero(0, 1), 1);
+        assert_eq!(fw.range_count_nonzero(6, 8), 0);
+        for idx in 0..=fw.end() {
+            let expect = usize::from(fw.get(idx) != 0);
+            assert_eq!(fw.range_count_nonzero(idx, idx), expect);
+        }
+        assert_eq!(fw.into_inner().total(), 17);
+    }
+}
@@ -0,0 +1,120 @@
+//! Immutable snapshots of a Fenwick Tree, shared between their clones.
+//!
+
+use std::ops::Add;
+use std::ops::Sub;
+use std::ops::AddAssign;
+use std::ops::SubAssign;
+use std::cmp::PartialOrd;
+use std::sync::Arc;
+
+use crate::{Fenwick, FenwickIter};
+
+/// A read-only snapshot of a `Fenwick` tree produced by `.to_view()`, which
+/// copies the tree's data once. Clones of a view share that copy through an
+/// `Arc`, so once a view is taken, handing out more of it is cheap even for
+/// large trees. Updates made to the source tree after the view was taken
+/// aren't visible through the view.
+///
+#[derive(Debug, Clone)]
+pub struct FenwickView<T> {
+    fw: Arc<Fenwick<T>>,
+}
+
+impl<T> FenwickView<T>
+where
+    T: Add<Output = T> + Sub<Output = T> + AddAssign + SubAssign +
+       PartialOrd + Default + Copy,
+{
+    /// Wraps the given tree, taking ownership of its data.
+    ///
+    pub(crate) fn new(fw: Fenwick<T>) -> Self {
+        FenwickView { fw: Arc::new(fw) }
+    }
+
+    /// Returns a non-consuming iterator over the snapshot's elements.
+    ///
+    pub fn iter(&self) -> FenwickIter<'_, T> {
+        self.fw.iter()
+    }
+
+    /// Returns the sum of the elements from index 0 to `idx` inclusive.
+    ///
+    pub fn prefix_sum(&self, idx: usize) -> T {
+        self.fw.prefix_sum(idx)
+    }
+
+    /// Returns the total prefix sum of all the elements.
+    ///
+    pub fn total(&self) -> T {
+        self.fw.total()
+    }
+
+    /// Returns the index of the last element.
+    ///
+    pub fn end(&self) -> usize {
+        self.fw.end()
+    }
+
+    /// Return a single element's value.
+    ///
+    pub fn get(&self, idx: usize) -> T {
+        self.fw.get(idx)
+    }
+
+    /// Returns the sum of elements from `idx_i` to `idx_j` inclusive.
+    ///
+    pub fn range_sum(&self, idx_i: usize, idx_j: usize) -> T {
+        self.fw.range_sum(idx_i, idx_j)
+    }
+
+    /// Returns the sum of elements from `idx_i` to `idx_j` non-inclusive.
+    ///
+    pub fn range_sum2(&self, idx_i: usize, idx_j: usize) -> T {
+        self.fw.range_sum2(idx_i, idx_j)
+    }
+
+    /// Find the largest index with `.prefix_sum(index) <= value`.
+    /// NOTE: Requires all values are non-negative.
+    ///
+    pub fn rank_query(&self, value: T) -> usize {
+        self.fw.rank_query(value)
+    }
+
+    /// Find the smallest index with `.prefix_sum(index) >= value`.
+    /// NOTE: Requires all values are non-negative.
+    ///
+    pub fn min_rank_query(&self, value: T) -> usize {
+        self.fw.min_rank_query(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn view_outlives_mutation() {
+        let mut fw = Fenwick::from_vec(vec![1, 1, 3, 1, 1]);
+        let view   = fw.to_view();
+        
+        fw.add(2, 10);
+        fw.set(0, 0);
+        assert_eq!(fw.total(), 16);
+        
+        assert_eq!(view.total(), 7);
+        assert_eq!(view.get(2), 3);
+        assert_eq!(view.prefix_sum(2), 5);
+        assert_eq!(view.range_sum(1, 3), 5);
+        assert_eq!(view.range_sum2(1, 3), 4);
+        assert_eq!(view.rank_query(5), 2);
+        assert_eq!(view.min_rank_query(6), 3);
+        assert_eq!(view.iter().nth(2), Some(3));
+        
+        let copy = view.clone();
+        drop(fw);
+        assert!(Arc::ptr_eq(&copy.fw, &view.fw));
+        assert_eq!(copy.total(), 7);
+    }
+}
@@ -0,0 +1,106 @@
+//! A fixed-capacity sliding window sum backed by a Fenwick Tree.
+//!
+
+use std::ops::Add;
+use std::ops::Sub;
+use std::ops::AddAssign;
+use std::ops::SubAssign;
+use std::cmp::PartialOrd;
+
+use crate::Fenwick;
+
+/// Maintains the sum of the most recent values pushed into it. The window's
+/// capacity is fixed at construction: once it holds `capacity` values, each
+/// push evicts the oldest value. The values are stored in a circular buffer
+/// inside a `Fenwick` tree, so pushes take `O(log n)` time.
+///
+#[derive(Debug, Clone)]
+pub struct SlidingWindow<T> {
+    fw       : Fenwick<T>,
+    capacity : usize,
+    start    : usize,
+    len      : usize,
+}
+
+impl<T> SlidingWindow<T>
+where
+    T: Add<Output = T> + Sub<Output = T> + AddAssign + SubAssign +
+       PartialOrd + Default + Copy,
+{
+    /// Creates an empty window that holds at most `capacity` values. Panics
+    /// if `capacity` is 0.
+    ///
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "The window capacity must be non-zero.");
+        SlidingWindow { fw: Fenwick::new(capacity), capacity, start: 0, len: 0 }
+    }
+
+    /// Pushes `value` into the window. If the window is already full, the
+    /// oldest value is evicted and returned.
+    ///
+    pub fn push(&mut self, value: T) -> Option<T> {
+        if self.len < self.capacity {
+            let idx = (self.start + self.len) % self.capacity;
+            self.fw.set(idx, value);
+            self.len += 1;
+            None
+        } else {
+            let evicted = self.fw.get(self.start);
+            self.fw.set(self.start, value);
+            self.start = (self.start + 1) % self.capacity;
+            Some(evicted)
+        }
+    }
+
+    /// Returns the sum of the values currently in the window.
+    ///
+    pub fn sum(&self) -> T {
+        self.fw.total()
+    }
+
+    /// Returns the number of values currently in the window.
+    ///
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if no values have been pushed into the window.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the maximum number of values the window holds.
+    ///
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::collections::VecDeque;
+
+    #[test]
+    fn sliding_window() {
+        let mut window = SlidingWindow::new(5);
+        let mut naive  = VecDeque::new();
+        assert!(window.is_empty());
+        
+        for n in 0..40_i64 {
+            let value   = (n * 37) % 13 - 4;
+            let evicted = window.push(value);
+            
+            naive.push_back(value);
+            if naive.len() > 5 {
+                assert_eq!(evicted, naive.pop_front());
+            } else {
+                assert_eq!(evicted, None);
+            }
+            assert_eq!(window.len(), naive.len());
+            assert_eq!(window.sum(), naive.iter().sum::<i64>());
+        }
+        assert_eq!(window.capacity(), 5);
+    }
+}